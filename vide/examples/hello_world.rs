@@ -40,7 +40,7 @@ fn main() {
         RenderConfiguration {
             resolution: RESOLUTION_1080P_16X9,
             frames_per_second: FPS_60,
-            hdr: false,
+            ..Default::default()
         },
         MediaExporter::new(Path::new("test-output/vide.mp4")),
         // ImageExporter::new(|frame| Path::new(&format!("test-output/{frame:04}.png")).to_path_buf()),
@@ -4,6 +4,7 @@ pub use euler;
 pub use vide_animate as animate;
 pub use vide_audio as audio;
 pub use vide_common as common;
+pub use vide_interchange as interchange;
 pub use vide_project as project;
 pub use vide_render as render;
 pub use vide_video as video;
@@ -1,4 +1,7 @@
 pub use vide_animate::prelude::*;
 pub use vide_common::prelude::*;
 pub use vide_project::prelude::*;
-pub use vide_render::render;
+pub use vide_render::{
+    batch::{render_batch, RenderVariant},
+    render,
+};
@@ -1,7 +1,10 @@
+use serde::{Deserialize, Serialize};
+
 use crate::ease::EaseSampler;
 
 // Mostly ported from https://chromium.googlesource.com/chromium/blink/+/master/Source/platform/animation/UnitBezier.h
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(from = "[f64; 4]", into = "[f64; 4]")]
 pub struct CubicBezier {
     cx: f64,
     bx: f64,
@@ -53,6 +56,19 @@ impl CubicBezier {
         }
     }
 
+    /// The control points this curve was created with, as `[x1, y1, x2, y2]`
+    pub const fn control_points(&self) -> [f64; 4] {
+        let point1_x = self.cx / 3.0;
+        let point1_y = self.cy / 3.0;
+
+        [
+            point1_x,
+            point1_y,
+            (self.bx + self.cx) / 3.0 + point1_x,
+            (self.by + self.cy) / 3.0 + point1_y,
+        ]
+    }
+
     pub const fn sample_curve_x(&self, t: f64) -> f64 {
         ((self.ax * t + self.bx) * t + self.cx) * t
     }
@@ -125,6 +141,18 @@ impl CubicBezier {
     }
 }
 
+impl From<[f64; 4]> for CubicBezier {
+    fn from([point1_x, point1_y, point2_x, point2_y]: [f64; 4]) -> Self {
+        Self::new(point1_x, point1_y, point2_x, point2_y)
+    }
+}
+
+impl From<CubicBezier> for [f64; 4] {
+    fn from(bezier: CubicBezier) -> Self {
+        bezier.control_points()
+    }
+}
+
 impl EaseSampler for CubicBezier {
     fn sample(&self, t: f64) -> f64 {
         self.solve(t, 1e-6)
@@ -1,6 +1,15 @@
-use std::fmt::Debug;
+use std::{
+    any::Any,
+    fmt::Debug,
+    sync::{LazyLock, RwLock},
+};
 
-pub trait EaseSampler: Debug + EaseSamplerClone {
+use serde::{de::DeserializeOwned, Serialize};
+use vide_common::registry::TypeRegistry;
+
+use crate::cubic_bezier::CubicBezier;
+
+pub trait EaseSampler: Debug + Any + EaseSamplerClone {
     fn sample(&self, t: f64) -> f64;
 }
 
@@ -25,3 +34,20 @@ impl<F: Fn(f64) -> f64 + Debug + Clone + 'static> EaseSampler for F {
         self(t)
     }
 }
+
+static EASE_SAMPLERS: LazyLock<RwLock<TypeRegistry<dyn EaseSampler>>> = LazyLock::new(|| {
+    let mut registry = TypeRegistry::<dyn EaseSampler>::new();
+    registry.register::<CubicBezier>("CubicBezier", |sampler| Box::new(sampler));
+    RwLock::new(registry)
+});
+
+/// Make `T` (de)serializable as a `Box<dyn EaseSampler>`, `tag` has to be unique.
+/// Closures can't be serialized.
+pub fn register_ease_sampler<T: EaseSampler + Serialize + DeserializeOwned>(tag: &'static str) {
+    EASE_SAMPLERS
+        .write()
+        .unwrap()
+        .register::<T>(tag, |sampler| Box::new(sampler));
+}
+
+vide_common::impl_registry_serde!(EaseSampler, EASE_SAMPLERS);
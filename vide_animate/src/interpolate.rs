@@ -1,5 +1,9 @@
 use euler::{DVec2, DVec3, DVec4, Quat, Vec2, Vec3, Vec4};
-use vide_common::{color::Color, transform::Transform};
+use vide_common::{
+    color::Color,
+    layout::{Anchor, Length, Position, Size},
+    transform::Transform,
+};
 
 pub trait Interpolate {
     fn interpolate(a: Self, b: Self, t: f64) -> Self;
@@ -162,3 +166,45 @@ impl Interpolate for Color {
         }
     }
 }
+
+impl Interpolate for Length {
+    fn interpolate(a: Self, b: Self, t: f64) -> Self {
+        Self {
+            px: f64::interpolate(a.px, b.px, t),
+            vw: f64::interpolate(a.vw, b.vw, t),
+            vh: f64::interpolate(a.vh, b.vh, t),
+            vmin: f64::interpolate(a.vmin, b.vmin, t),
+            vmax: f64::interpolate(a.vmax, b.vmax, t),
+        }
+    }
+}
+
+impl Interpolate for Anchor {
+    fn interpolate(a: Self, b: Self, t: f64) -> Self {
+        Self {
+            x: f64::interpolate(a.x, b.x, t),
+            y: f64::interpolate(a.y, b.y, t),
+            // Safe areas can't be blended, hold the previous one until the keyframe is reached
+            area: if t < 1.0 { a.area } else { b.area },
+        }
+    }
+}
+
+impl Interpolate for Position {
+    fn interpolate(a: Self, b: Self, t: f64) -> Self {
+        Self {
+            x: a.x.interpolate_to(b.x, t),
+            y: a.y.interpolate_to(b.y, t),
+            anchor: a.anchor.interpolate_to(b.anchor, t),
+        }
+    }
+}
+
+impl Interpolate for Size {
+    fn interpolate(a: Self, b: Self, t: f64) -> Self {
+        Self {
+            width: a.width.interpolate_to(b.width, t),
+            height: a.height.interpolate_to(b.height, t),
+        }
+    }
+}
@@ -5,15 +5,18 @@ use std::fmt::Debug;
 
 use ease::EaseSampler;
 use prelude::Interpolate;
+use serde::{Deserialize, Serialize};
 use vide_common::prelude::TimeCode;
 
 pub mod cubic_bezier;
 pub mod ease;
 pub mod interpolate;
 pub mod prelude;
+pub mod serialize;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Keyframe<T: Interpolate + Debug + Clone> {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     easing: Option<Box<dyn EaseSampler>>,
     time_code: TimeCode,
     value: T,
@@ -32,9 +35,10 @@ impl<T: Interpolate + Debug + Clone> Keyframe<T> {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AnimatedProperty<T: Interpolate + Debug + Clone> {
     default: T,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     keyframes: Vec<Keyframe<T>>,
 }
 
@@ -54,6 +58,33 @@ impl<T: Interpolate + Debug + Clone> AnimatedProperty<T> {
         self.keyframes.push(keyframe);
     }
 
+    /// The time of the latest keyframe, if there are any
+    pub fn last_keyframe_time(&self) -> Option<TimeCode> {
+        self.keyframes
+            .iter()
+            .map(|keyframe| keyframe.time_code)
+            .max()
+    }
+
+    /// Convert the default and every keyframe value, keeping timing and easing
+    pub fn map<U: Interpolate + Debug + Clone>(
+        &self,
+        mut f: impl FnMut(&T) -> U,
+    ) -> AnimatedProperty<U> {
+        AnimatedProperty {
+            default: f(&self.default),
+            keyframes: self
+                .keyframes
+                .iter()
+                .map(|keyframe| Keyframe {
+                    easing: keyframe.easing.clone(),
+                    time_code: keyframe.time_code,
+                    value: f(&keyframe.value),
+                })
+                .collect(),
+        }
+    }
+
     pub fn evaluate(&self, time_code: TimeCode) -> T {
         if let Some(keyframe) = self.keyframes.first() {
             if keyframe.time_code >= time_code {
@@ -0,0 +1,23 @@
+//! Serde helpers for animated properties of types that can't be serialized directly,
+//! use them with `#[serde(with = "...")]`
+
+/// `AnimatedProperty<Vec2>` as `(x, y)` tuples
+pub mod vec2 {
+    use euler::{vec2, Vec2};
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use crate::AnimatedProperty;
+
+    pub fn serialize<S: Serializer>(
+        property: &AnimatedProperty<Vec2>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        property.map(|v| (v.x, v.y)).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<AnimatedProperty<Vec2>, D::Error> {
+        Ok(AnimatedProperty::<(f32, f32)>::deserialize(deserializer)?.map(|&(x, y)| vec2!(x, y)))
+    }
+}
@@ -0,0 +1,27 @@
+use serde::{Deserialize, Serialize};
+
+/// How a clip is composited onto the clips below it, matching the blend modes
+/// found in most compositing software
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum BlendMode {
+    #[default]
+    Normal,
+    Darken,
+    Multiply,
+    ColorBurn,
+    Lighten,
+    Screen,
+    ColorDodge,
+    /// Also known as linear dodge
+    Add,
+    Overlay,
+    SoftLight,
+    HardLight,
+    Difference,
+    Exclusion,
+    Subtract,
+    Hue,
+    Saturation,
+    Color,
+    Luminosity,
+}
@@ -1,5 +1,7 @@
+use serde::{Deserialize, Serialize};
+
 /// Holds RGBA values converted to SRGB color space
-#[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct Color {
     /// Amount of red in the color
     pub r: f64,
@@ -87,6 +89,17 @@ impl From<Color> for [f64; 4] {
     }
 }
 
+impl From<Color> for wgpu::Color {
+    fn from(col: Color) -> Self {
+        Self {
+            r: col.r,
+            g: col.g,
+            b: col.b,
+            a: col.a,
+        }
+    }
+}
+
 impl From<&str> for Color {
     fn from(string: &str) -> Self {
         match string.chars().collect::<Vec<char>>().as_slice() {
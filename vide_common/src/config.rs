@@ -1,4 +1,10 @@
-use crate::types::{FramesPerSecond, Resolution};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    color::Color,
+    time_code::UnboundedTimecodeRange,
+    types::{Frames, FramesPerSecond, Resolution},
+};
 
 pub mod presets {
     use crate::types::{FramesPerSecond, Resolution};
@@ -27,9 +33,176 @@ pub mod presets {
     pub const FPS_240: FramesPerSecond = 240.0;
 }
 
-#[derive(Debug, Clone, Copy)]
+/// What rendering does with the problems project validation finds
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Strictness {
+    /// Don't validate the project
+    Off,
+    /// Log problems and render anyway
+    #[default]
+    Warn,
+    /// Refuse to render projects with errors, log warnings
+    Deny,
+    /// Refuse to render projects with errors or warnings
+    DenyWarnings,
+}
+
+/// Which part of a project is rendered, always limited to the project duration
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize)]
+pub enum RenderRange {
+    #[default]
+    Full,
+    /// The work area of the project, or everything if it doesn't have one
+    WorkArea,
+    Time(UnboundedTimecodeRange),
+    /// `end` is exclusive
+    Frames {
+        start: Frames,
+        end: Frames,
+    },
+}
+
+/// How the canvas of a project is mapped to the output resolution when they differ
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Scaling {
+    /// Scale the canvas to fit inside the output, the rest stays transparent
+    #[default]
+    Fit,
+    /// Scale the canvas to cover the output, cutting off its edges
+    Fill,
+    /// Scale the width and height separately to match the output exactly
+    Stretch,
+    /// Like [`Scaling::Fit`], with bars of a color around the canvas
+    Letterbox(Color),
+}
+
+impl Scaling {
+    /// The scale and offset (in output pixels) that map the canvas onto the output
+    pub fn map(&self, canvas: Resolution, output: Resolution) -> ((f64, f64), (f64, f64)) {
+        let scale_x = output.0 as f64 / canvas.0 as f64;
+        let scale_y = output.1 as f64 / canvas.1 as f64;
+
+        let scale = match self {
+            Self::Fit | Self::Letterbox(_) => scale_x.min(scale_y),
+            Self::Fill => scale_x.max(scale_y),
+            Self::Stretch => return ((scale_x, scale_y), (0.0, 0.0)),
+        };
+
+        (
+            (scale, scale),
+            (
+                (output.0 as f64 - canvas.0 as f64 * scale) / 2.0,
+                (output.1 as f64 - canvas.1 as f64 * scale) / 2.0,
+            ),
+        )
+    }
+
+    /// The color around the canvas
+    pub fn bars(&self) -> Color {
+        match self {
+            Self::Letterbox(color) => *color,
+            _ => Color::TRANSPARENT,
+        }
+    }
+}
+
+fn default_frame_step() -> Frames {
+    1
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct RenderConfiguration {
     pub resolution: Resolution,
     pub frames_per_second: FramesPerSecond,
     pub hdr: bool,
+    #[serde(default)]
+    pub strictness: Strictness,
+    #[serde(default)]
+    pub range: RenderRange,
+    /// Only render every nth frame, for quick drafts. Each rendered frame is shown for
+    /// n frames, so the output keeps its duration.
+    #[serde(default = "default_frame_step")]
+    pub frame_step: Frames,
+    /// Used when the project canvas doesn't match `resolution`
+    #[serde(default)]
+    pub scaling: Scaling,
+}
+
+impl Default for RenderConfiguration {
+    fn default() -> Self {
+        Self {
+            resolution: presets::RESOLUTION_1080P_16X9,
+            frames_per_second: presets::FPS_60,
+            hdr: false,
+            strictness: Strictness::default(),
+            range: RenderRange::default(),
+            frame_step: default_frame_step(),
+            scaling: Scaling::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SQUARE: Resolution = (100, 100);
+
+    #[test]
+    fn wide_canvas() {
+        let canvas = (200, 100);
+
+        assert_eq!(Scaling::Fit.map(canvas, SQUARE), ((0.5, 0.5), (0.0, 25.0)));
+        assert_eq!(
+            Scaling::Letterbox(Color::BLACK).map(canvas, SQUARE),
+            ((0.5, 0.5), (0.0, 25.0))
+        );
+        assert_eq!(
+            Scaling::Fill.map(canvas, SQUARE),
+            ((1.0, 1.0), (-50.0, 0.0))
+        );
+        assert_eq!(
+            Scaling::Stretch.map(canvas, SQUARE),
+            ((0.5, 1.0), (0.0, 0.0))
+        );
+    }
+
+    #[test]
+    fn narrow_canvas() {
+        let canvas = (100, 200);
+
+        assert_eq!(Scaling::Fit.map(canvas, SQUARE), ((0.5, 0.5), (25.0, 0.0)));
+        assert_eq!(
+            Scaling::Letterbox(Color::BLACK).map(canvas, SQUARE),
+            ((0.5, 0.5), (25.0, 0.0))
+        );
+        assert_eq!(
+            Scaling::Fill.map(canvas, SQUARE),
+            ((1.0, 1.0), (0.0, -50.0))
+        );
+        assert_eq!(
+            Scaling::Stretch.map(canvas, SQUARE),
+            ((1.0, 0.5), (0.0, 0.0))
+        );
+    }
+
+    #[test]
+    fn matching_canvas() {
+        for scaling in [Scaling::Fit, Scaling::Fill, Scaling::Stretch] {
+            assert_eq!(
+                scaling.map((1920, 1080), (1920, 1080)),
+                ((1.0, 1.0), (0.0, 0.0))
+            );
+            assert_eq!(
+                scaling.map((1920, 1080), (3840, 2160)),
+                ((2.0, 2.0), (0.0, 0.0))
+            );
+        }
+    }
+
+    #[test]
+    fn bars() {
+        assert_eq!(Scaling::Letterbox(Color::BLACK).bars(), Color::BLACK);
+        assert_eq!(Scaling::Fit.bars(), Color::TRANSPARENT);
+    }
 }
@@ -0,0 +1,54 @@
+use std::{
+    any::Any,
+    fmt::Debug,
+    sync::{LazyLock, RwLock},
+};
+
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{config::RenderConfiguration, registry::TypeRegistry, render::Wgpu, FrameInfo};
+
+/// A GPU pass that processes the rendered output of a clip
+pub trait Effect: Debug + Any + EffectClone {
+    fn init(&mut self, wgpu: &Wgpu, config: &RenderConfiguration);
+    /// Read `source` and write the processed result to `destination`, both have the
+    /// resolution of the render and the [`FRAGMENT_COLOR_TARGET`](crate::standards::FRAGMENT_COLOR_TARGET) format
+    fn apply(
+        &mut self,
+        wgpu: &Wgpu,
+        frame_info: &FrameInfo,
+        local_frame_info: &FrameInfo,
+        encoder: &mut wgpu::CommandEncoder,
+        source: &wgpu::TextureView,
+        destination: &wgpu::TextureView,
+    );
+}
+
+pub trait EffectClone {
+    fn clone_effect(&self) -> Box<dyn Effect>;
+}
+
+impl<T: 'static + Effect + Clone> EffectClone for T {
+    fn clone_effect(&self) -> Box<dyn Effect> {
+        Box::new(self.clone())
+    }
+}
+
+impl Clone for Box<dyn Effect> {
+    fn clone(&self) -> Self {
+        self.clone_effect()
+    }
+}
+
+static EFFECTS: LazyLock<RwLock<TypeRegistry<dyn Effect>>> =
+    LazyLock::new(|| RwLock::new(TypeRegistry::new()));
+
+/// Make `T` (de)serializable as a `Box<dyn Effect>`, `tag` has to be unique
+pub fn register_effect<T: Effect + Serialize + DeserializeOwned>(tag: &'static str) {
+    EFFECTS
+        .write()
+        .unwrap()
+        .register::<T>(tag, |effect| Box::new(effect));
+}
+
+crate::impl_registry_serde!(Effect, EFFECTS);
@@ -0,0 +1,418 @@
+use std::ops::{Add, Mul, Neg, Sub};
+
+use euler::{vec2, Vec2};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    standards::{ACTION_SAFE_MARGIN, TITLE_SAFE_MARGIN},
+    types::Resolution,
+};
+
+/// A length that is resolved against the frame resolution at render time.
+///
+/// All components are summed when resolving, so `Length::vw(50.0) - Length::px(20.0)`
+/// means "half of the frame width minus 20 pixels".
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Length {
+    /// Absolute pixels
+    pub px: f64,
+    /// Percentage of the frame width
+    pub vw: f64,
+    /// Percentage of the frame height
+    pub vh: f64,
+    /// Percentage of the smallest frame dimension
+    pub vmin: f64,
+    /// Percentage of the largest frame dimension
+    pub vmax: f64,
+}
+
+impl Length {
+    pub const ZERO: Length = Length {
+        px: 0.0,
+        vw: 0.0,
+        vh: 0.0,
+        vmin: 0.0,
+        vmax: 0.0,
+    };
+
+    pub const fn px(value: f64) -> Self {
+        Self {
+            px: value,
+            ..Self::ZERO
+        }
+    }
+
+    pub const fn vw(value: f64) -> Self {
+        Self {
+            vw: value,
+            ..Self::ZERO
+        }
+    }
+
+    pub const fn vh(value: f64) -> Self {
+        Self {
+            vh: value,
+            ..Self::ZERO
+        }
+    }
+
+    pub const fn vmin(value: f64) -> Self {
+        Self {
+            vmin: value,
+            ..Self::ZERO
+        }
+    }
+
+    pub const fn vmax(value: f64) -> Self {
+        Self {
+            vmax: value,
+            ..Self::ZERO
+        }
+    }
+
+    /// Resolve this length to pixels
+    pub fn resolve(&self, resolution: Resolution) -> f64 {
+        let width = resolution.0 as f64;
+        let height = resolution.1 as f64;
+
+        self.px
+            + self.vw * width / 100.0
+            + self.vh * height / 100.0
+            + self.vmin * width.min(height) / 100.0
+            + self.vmax * width.max(height) / 100.0
+    }
+}
+
+impl Add for Length {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self {
+            px: self.px + rhs.px,
+            vw: self.vw + rhs.vw,
+            vh: self.vh + rhs.vh,
+            vmin: self.vmin + rhs.vmin,
+            vmax: self.vmax + rhs.vmax,
+        }
+    }
+}
+
+impl Sub for Length {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        self + -rhs
+    }
+}
+
+impl Neg for Length {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        self * -1.0
+    }
+}
+
+impl Mul<f64> for Length {
+    type Output = Self;
+
+    fn mul(self, rhs: f64) -> Self::Output {
+        Self {
+            px: self.px * rhs,
+            vw: self.vw * rhs,
+            vh: self.vh * rhs,
+            vmin: self.vmin * rhs,
+            vmax: self.vmax * rhs,
+        }
+    }
+}
+
+impl From<f64> for Length {
+    fn from(value: f64) -> Self {
+        Self::px(value)
+    }
+}
+
+impl From<f32> for Length {
+    fn from(value: f32) -> Self {
+        Self::px(value as f64)
+    }
+}
+
+impl From<i32> for Length {
+    fn from(value: i32) -> Self {
+        Self::px(value as f64)
+    }
+}
+
+/// The part of the frame an [`Anchor`] is relative to
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SafeArea {
+    /// The full frame
+    #[default]
+    Frame,
+    /// The action safe area, see [`ACTION_SAFE_MARGIN`]
+    Action,
+    /// The title safe area, see [`TITLE_SAFE_MARGIN`]
+    Title,
+}
+
+impl SafeArea {
+    /// Fraction of the frame size that is cut off at every edge
+    pub fn margin(&self) -> f64 {
+        match self {
+            SafeArea::Frame => 0.0,
+            SafeArea::Action => ACTION_SAFE_MARGIN,
+            SafeArea::Title => TITLE_SAFE_MARGIN,
+        }
+    }
+
+    /// Returns the bottom-left corner and the size of this area in pixels
+    pub fn bounds(&self, resolution: Resolution) -> (Vec2, Vec2) {
+        let width = resolution.0 as f64;
+        let height = resolution.1 as f64;
+        let margin = self.margin();
+
+        (
+            vec2!(width * margin, height * margin),
+            vec2!(width * (1.0 - 2.0 * margin), height * (1.0 - 2.0 * margin)),
+        )
+    }
+}
+
+/// A point on the frame (or one of its safe areas) that positions are measured from.
+///
+/// `x` and `y` are normalized, `(0.0, 0.0)` is the bottom-left corner and `(1.0, 1.0)`
+/// is the top-right corner, the same orientation as the render space.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Anchor {
+    pub x: f64,
+    pub y: f64,
+    pub area: SafeArea,
+}
+
+impl Anchor {
+    pub const TOP_LEFT: Anchor = Anchor::new(0.0, 1.0);
+    pub const TOP: Anchor = Anchor::new(0.5, 1.0);
+    pub const TOP_RIGHT: Anchor = Anchor::new(1.0, 1.0);
+    pub const LEFT: Anchor = Anchor::new(0.0, 0.5);
+    pub const CENTER: Anchor = Anchor::new(0.5, 0.5);
+    pub const RIGHT: Anchor = Anchor::new(1.0, 0.5);
+    pub const BOTTOM_LEFT: Anchor = Anchor::new(0.0, 0.0);
+    pub const BOTTOM: Anchor = Anchor::new(0.5, 0.0);
+    pub const BOTTOM_RIGHT: Anchor = Anchor::new(1.0, 0.0);
+
+    pub const fn new(x: f64, y: f64) -> Self {
+        Self {
+            x,
+            y,
+            area: SafeArea::Frame,
+        }
+    }
+
+    /// Make this anchor relative to a safe area instead of the full frame
+    pub const fn within(self, area: SafeArea) -> Self {
+        Self { area, ..self }
+    }
+
+    /// Resolve this anchor to a point in pixels
+    pub fn resolve(&self, resolution: Resolution) -> Vec2 {
+        let (origin, size) = self.area.bounds(resolution);
+
+        vec2!(
+            origin.x as f64 + size.x as f64 * self.x,
+            origin.y as f64 + size.y as f64 * self.y
+        )
+    }
+}
+
+impl Default for Anchor {
+    fn default() -> Self {
+        Self::BOTTOM_LEFT
+    }
+}
+
+/// A resolution-independent position, made up of an [`Anchor`] and an offset from it.
+///
+/// Offsets follow the render space, so a positive `y` always moves up, even when
+/// anchored to the top of the frame.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Position {
+    pub x: Length,
+    pub y: Length,
+    pub anchor: Anchor,
+}
+
+impl Position {
+    pub fn new(anchor: Anchor, x: impl Into<Length>, y: impl Into<Length>) -> Self {
+        Self {
+            x: x.into(),
+            y: y.into(),
+            anchor,
+        }
+    }
+
+    /// Resolve this position to a point in pixels
+    pub fn resolve(&self, resolution: Resolution) -> Vec2 {
+        self.anchor.resolve(resolution)
+            + vec2!(self.x.resolve(resolution), self.y.resolve(resolution))
+    }
+}
+
+impl From<Vec2> for Position {
+    fn from(value: Vec2) -> Self {
+        Self::new(Anchor::BOTTOM_LEFT, value.x, value.y)
+    }
+}
+
+impl From<Anchor> for Position {
+    fn from(anchor: Anchor) -> Self {
+        Self::new(anchor, Length::ZERO, Length::ZERO)
+    }
+}
+
+/// A resolution-independent size
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Size {
+    pub width: Length,
+    pub height: Length,
+}
+
+impl Size {
+    pub fn new(width: impl Into<Length>, height: impl Into<Length>) -> Self {
+        Self {
+            width: width.into(),
+            height: height.into(),
+        }
+    }
+
+    /// Resolve this size to pixels
+    pub fn resolve(&self, resolution: Resolution) -> Vec2 {
+        vec2!(
+            self.width.resolve(resolution),
+            self.height.resolve(resolution)
+        )
+    }
+}
+
+impl From<Vec2> for Size {
+    fn from(value: Vec2) -> Self {
+        Self::new(value.x, value.y)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LANDSCAPE: Resolution = (1920, 1080);
+    const PORTRAIT: Resolution = (1080, 1920);
+
+    fn assert_close(actual: Vec2, expected: (f64, f64)) {
+        assert!(
+            (actual.x as f64 - expected.0).abs() < 1e-3
+                && (actual.y as f64 - expected.1).abs() < 1e-3,
+            "{actual:?} != {expected:?}"
+        );
+    }
+
+    #[test]
+    fn length_units() {
+        for (resolution, vw, vh, vmin, vmax) in [
+            (LANDSCAPE, 192.0, 108.0, 108.0, 192.0),
+            (PORTRAIT, 108.0, 192.0, 108.0, 192.0),
+        ] {
+            assert_eq!(Length::px(12.5).resolve(resolution), 12.5);
+            assert_eq!(Length::vw(10.0).resolve(resolution), vw);
+            assert_eq!(Length::vh(10.0).resolve(resolution), vh);
+            assert_eq!(Length::vmin(10.0).resolve(resolution), vmin);
+            assert_eq!(Length::vmax(10.0).resolve(resolution), vmax);
+        }
+    }
+
+    #[test]
+    fn length_arithmetic() {
+        let length = Length::vw(50.0) - Length::px(20.0);
+        assert_eq!(length.resolve(LANDSCAPE), 940.0);
+        assert_eq!(length.resolve(PORTRAIT), 520.0);
+
+        assert_eq!((-Length::vh(10.0)).resolve(LANDSCAPE), -108.0);
+        assert_eq!((Length::vmax(5.0) * 2.0).resolve(PORTRAIT), 192.0);
+        assert_eq!(Length::from(3).resolve(PORTRAIT), 3.0);
+    }
+
+    #[test]
+    fn anchors() {
+        for (resolution, (width, height)) in
+            [(LANDSCAPE, (1920.0, 1080.0)), (PORTRAIT, (1080.0, 1920.0))]
+        {
+            let cases = [
+                (Anchor::TOP_LEFT, (0.0, height)),
+                (Anchor::TOP, (width / 2.0, height)),
+                (Anchor::TOP_RIGHT, (width, height)),
+                (Anchor::LEFT, (0.0, height / 2.0)),
+                (Anchor::CENTER, (width / 2.0, height / 2.0)),
+                (Anchor::RIGHT, (width, height / 2.0)),
+                (Anchor::BOTTOM_LEFT, (0.0, 0.0)),
+                (Anchor::BOTTOM, (width / 2.0, 0.0)),
+                (Anchor::BOTTOM_RIGHT, (width, 0.0)),
+            ];
+
+            for (anchor, expected) in cases {
+                assert_close(anchor.resolve(resolution), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn safe_areas() {
+        for (resolution, (width, height)) in
+            [(LANDSCAPE, (1920.0, 1080.0)), (PORTRAIT, (1080.0, 1920.0))]
+        {
+            for (area, margin) in [
+                (SafeArea::Frame, 0.0),
+                (SafeArea::Action, ACTION_SAFE_MARGIN),
+                (SafeArea::Title, TITLE_SAFE_MARGIN),
+            ] {
+                let (origin, size) = area.bounds(resolution);
+                assert_close(origin, (width * margin, height * margin));
+                assert_close(
+                    size,
+                    (width * (1.0 - 2.0 * margin), height * (1.0 - 2.0 * margin)),
+                );
+
+                assert_close(
+                    Anchor::TOP_RIGHT.within(area).resolve(resolution),
+                    (width * (1.0 - margin), height * (1.0 - margin)),
+                );
+                assert_close(
+                    Anchor::BOTTOM_LEFT.within(area).resolve(resolution),
+                    (width * margin, height * margin),
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn positions_and_sizes() {
+        // 20 pixels left of and 5% of the height below the top-right corner
+        let position = Position::new(Anchor::TOP_RIGHT, -20.0, Length::vh(-5.0));
+        assert_close(position.resolve(LANDSCAPE), (1900.0, 1026.0));
+        assert_close(position.resolve(PORTRAIT), (1060.0, 1824.0));
+
+        let position = Position::from(vec2!(10.0, 20.0));
+        assert_close(position.resolve(LANDSCAPE), (10.0, 20.0));
+        assert_close(
+            Position::from(Anchor::CENTER).resolve(PORTRAIT),
+            (540.0, 960.0),
+        );
+
+        let size = Size::new(Length::vmin(50.0), Length::vmax(25.0) + Length::px(10.0));
+        assert_close(size.resolve(LANDSCAPE), (540.0, 490.0));
+        assert_close(size.resolve(PORTRAIT), (540.0, 490.0));
+
+        let size = Size::new(Length::vw(100.0), Length::vh(100.0));
+        assert_close(size.resolve(LANDSCAPE), (1920.0, 1080.0));
+        assert_close(size.resolve(PORTRAIT), (1080.0, 1920.0));
+    }
+}
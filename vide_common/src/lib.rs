@@ -2,9 +2,13 @@ use prelude::TimeCode;
 use time_code::UnboundedTimecodeRange;
 use types::{Resolution, TimeUnit};
 
+pub mod blend_mode;
 pub mod color;
 pub mod config;
+pub mod effect;
+pub mod layout;
 pub mod prelude;
+pub mod registry;
 pub mod render;
 pub mod standards;
 pub mod time_code;
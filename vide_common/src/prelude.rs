@@ -1,4 +1,6 @@
+pub use crate::blend_mode::BlendMode;
 pub use crate::color::Color;
-pub use crate::config::{self, presets::*, RenderConfiguration};
+pub use crate::config::{self, presets::*, RenderConfiguration, RenderRange, Scaling, Strictness};
+pub use crate::layout::{Anchor, Length, Position, SafeArea, Size};
 pub use crate::time_code::TimeCode;
 pub use crate::types::TimeUnit::*;
@@ -0,0 +1,124 @@
+//! Serialization of trait objects. Implementations are registered under a type tag and
+//! written as `{ "type": tag, "value": ... }`, so they can be read back into the right type.
+
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    fmt::Debug,
+};
+
+use serde::{de::DeserializeOwned, Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+
+type SerializeFn = fn(&dyn Any) -> serde_json::Result<Value>;
+type DeserializeFn<T> = Box<dyn Fn(Value) -> serde_json::Result<Box<T>> + Send + Sync>;
+
+#[derive(Serialize, Deserialize)]
+struct Tagged<V> {
+    #[serde(rename = "type")]
+    tag: String,
+    value: V,
+}
+
+/// The types that can be (de)serialized as a `Box<T>`
+pub struct TypeRegistry<T: ?Sized> {
+    serializers: HashMap<TypeId, (&'static str, SerializeFn)>,
+    deserializers: HashMap<&'static str, DeserializeFn<T>>,
+}
+
+impl<T: ?Sized + 'static> TypeRegistry<T> {
+    pub fn new() -> Self {
+        Self {
+            serializers: HashMap::new(),
+            deserializers: HashMap::new(),
+        }
+    }
+
+    /// Register `U` under `tag`, `boxed` turns it into the trait object
+    pub fn register<U: Serialize + DeserializeOwned + 'static>(
+        &mut self,
+        tag: &'static str,
+        boxed: fn(U) -> Box<T>,
+    ) {
+        self.serializers.insert(
+            TypeId::of::<U>(),
+            (tag, |object| {
+                serde_json::to_value(
+                    object
+                        .downcast_ref::<U>()
+                        .expect("serializer is registered by type id"),
+                )
+            }),
+        );
+        self.deserializers.insert(
+            tag,
+            Box::new(move |value| Ok(boxed(serde_json::from_value(value)?))),
+        );
+    }
+
+    pub fn serialize<S: Serializer>(
+        &self,
+        object: &dyn Any,
+        trait_name: &str,
+        debug: &dyn Debug,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let Some((tag, serialize)) = self.serializers.get(&object.type_id()) else {
+            return Err(serde::ser::Error::custom(format!(
+                "{trait_name} {debug:?} is not registered and can't be serialized"
+            )));
+        };
+
+        Tagged {
+            tag: tag.to_string(),
+            value: serialize(object).map_err(serde::ser::Error::custom)?,
+        }
+        .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        &self,
+        deserializer: D,
+    ) -> Result<Box<T>, D::Error> {
+        let tagged = Tagged::<Value>::deserialize(deserializer)?;
+
+        let Some(deserialize) = self.deserializers.get(tagged.tag.as_str()) else {
+            return Err(serde::de::Error::custom(format!(
+                "unknown type `{}`, it might not be registered",
+                tagged.tag
+            )));
+        };
+
+        deserialize(tagged.value).map_err(serde::de::Error::custom)
+    }
+}
+
+impl<T: ?Sized + 'static> Default for TypeRegistry<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Implement `Serialize` for `dyn $trait` and `Deserialize` for `Box<dyn $trait>` using
+/// the registry in `$registry`, a `LazyLock<RwLock<TypeRegistry<dyn $trait>>>`
+#[macro_export]
+macro_rules! impl_registry_serde {
+    ($trait:ident, $registry:ident) => {
+        impl serde::Serialize for dyn $trait {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                $registry.read().unwrap().serialize(
+                    self as &dyn std::any::Any,
+                    stringify!($trait),
+                    self as &dyn std::fmt::Debug,
+                    serializer,
+                )
+            }
+        }
+
+        impl<'de> serde::Deserialize<'de> for Box<dyn $trait> {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                $registry.read().unwrap().deserialize(deserializer)
+            }
+        }
+    };
+}
@@ -1,3 +1,70 @@
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
+
+type Resource = Arc<dyn Any + Send + Sync>;
+
+/// Resources that all objects of a type share on one device, like pipelines. Every
+/// [`Wgpu`] has its own, so nothing is reused across devices or renders.
+///
+/// Resources are keyed by the type that owns them (usually the object or effect) and
+/// the texture format they render to.
+#[derive(Debug, Default)]
+pub struct ResourceCache {
+    resources: RwLock<HashMap<(TypeId, wgpu::TextureFormat), Resource>>,
+}
+
+impl ResourceCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The resource of `O` for `format`, created with `init` if it doesn't exist yet
+    pub fn get_or_init<O: 'static, T: Any + Send + Sync>(
+        &self,
+        format: wgpu::TextureFormat,
+        init: impl FnOnce() -> T,
+    ) -> Arc<T> {
+        if let Some(resource) = self.get::<O, T>(format) {
+            return resource;
+        }
+
+        // Created outside of the lock, `init` may need other resources
+        let resource = Arc::new(init());
+
+        let mut resources = self.resources.write().unwrap();
+        let resource = resources
+            .entry((TypeId::of::<O>(), format))
+            .or_insert(resource);
+
+        Self::downcast(resource.clone())
+    }
+
+    /// The resource of `O` for `format`, if [`ResourceCache::get_or_init`] created it
+    pub fn get<O: 'static, T: Any + Send + Sync>(
+        &self,
+        format: wgpu::TextureFormat,
+    ) -> Option<Arc<T>> {
+        self.resources
+            .read()
+            .unwrap()
+            .get(&(TypeId::of::<O>(), format))
+            .map(|resource| Self::downcast(resource.clone()))
+    }
+
+    pub fn clear(&self) {
+        self.resources.write().unwrap().clear();
+    }
+
+    fn downcast<T: Any + Send + Sync>(resource: Resource) -> Arc<T> {
+        resource
+            .downcast()
+            .unwrap_or_else(|_| panic!("cached resource is not a {}", std::any::type_name::<T>()))
+    }
+}
+
 #[derive(Debug)]
 pub struct Wgpu {
     pub instance: wgpu::Instance,
@@ -6,6 +73,7 @@ pub struct Wgpu {
     pub queue: wgpu::Queue,
     pub global_bind_group_layout: wgpu::BindGroupLayout,
     pub global_bind_group: wgpu::BindGroup,
+    pub resources: ResourceCache,
 }
 
 #[repr(C)]
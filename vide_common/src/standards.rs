@@ -1,2 +1,7 @@
 pub const FRAGMENT_COLOR_TARGET: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
 pub const FRAGMENT_DEPTH_TARGET: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+
+/// Action safe margin on every edge of the frame (EBU R 95)
+pub const ACTION_SAFE_MARGIN: f64 = 0.035;
+/// Title (graphics) safe margin on every edge of the frame (EBU R 95)
+pub const TITLE_SAFE_MARGIN: f64 = 0.05;
@@ -3,9 +3,12 @@ use std::{
     ops::{Add, Range, RangeFrom, RangeFull, RangeTo, Sub},
 };
 
+use serde::{Deserialize, Serialize};
+
 use crate::types::{Frames, FramesPerSecond, Seconds, TimeUnit};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default, Serialize, Deserialize)]
+#[serde(transparent)]
 pub struct TimeCode {
     value: i64,
 }
@@ -19,6 +22,10 @@ impl TimeCode {
         Self { value }
     }
 
+    pub fn from_seconds(seconds: Seconds) -> Self {
+        TimeUnit::Seconds(seconds).into()
+    }
+
     pub fn seconds(&self) -> Seconds {
         self.value as Seconds / Self::SECOND as Seconds
     }
@@ -69,7 +76,7 @@ impl Display for TimeCode {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct UnboundedTimecodeRange {
     start: Option<TimeCode>,
     end: Option<TimeCode>,
@@ -88,6 +95,20 @@ impl UnboundedTimecodeRange {
         self.end
     }
 
+    pub fn set_start(&mut self, start: Option<TimeCode>) {
+        self.start = start;
+    }
+
+    pub fn set_end(&mut self, end: Option<TimeCode>) {
+        self.end = end;
+    }
+
+    /// Move both ends of the range by `offset`, an unbounded start is treated as 0
+    pub fn shift(&mut self, offset: TimeCode) {
+        self.start = Some(self.start.unwrap_or_default() + offset);
+        self.end = self.end.map(|end| end + offset);
+    }
+
     pub fn duration(&self) -> Option<TimeCode> {
         Some(self.end? - self.start?)
     }
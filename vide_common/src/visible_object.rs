@@ -1,9 +1,18 @@
-use std::fmt::Debug;
+use std::{
+    any::Any,
+    fmt::Debug,
+    sync::{LazyLock, RwLock},
+};
 
-use crate::{config::RenderConfiguration, render::Wgpu, time_code::TimeCode, FrameInfo};
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{
+    config::RenderConfiguration, registry::TypeRegistry, render::Wgpu, time_code::TimeCode,
+    FrameInfo,
+};
 use euler::Mat4;
 
-pub trait VisibleObject: Debug {
+pub trait VisibleObject: Debug + Any + VisibleObjectClone {
     fn init(&mut self, wgpu: &Wgpu, config: &RenderConfiguration);
     fn duration(&self) -> Option<TimeCode>;
     fn set_transform(&mut self, transform: Mat4);
@@ -16,4 +25,61 @@ pub trait VisibleObject: Debug {
         encoder: &mut wgpu::CommandEncoder,
         destination: &wgpu::TextureView,
     );
+
+    /// Whether [`VisibleObject::render`] writes premultiplied alpha instead of straight
+    /// alpha
+    fn premultiplied(&self) -> bool {
+        false
+    }
+
+    /// Whether this object implements [`VisibleObject::draw`]. Clips with such a video
+    /// and nothing else that needs compositing (children, masks, effects, a track matte,
+    /// a blend mode or opacity) are drawn together in a single pass.
+    fn batched(&self) -> bool {
+        false
+    }
+
+    /// Record the draw calls of this object into `pass`, which other objects share.
+    /// Output premultiplied alpha and blend it over what is already there, usually with
+    /// [`wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING`]. Only called if
+    /// [`VisibleObject::batched`] returns `true`, after [`VisibleObject::update`].
+    /// The default draws nothing.
+    fn draw(
+        &mut self,
+        wgpu: &Wgpu,
+        frame_info: &FrameInfo,
+        local_frame_info: &FrameInfo,
+        pass: &mut wgpu::RenderPass<'_>,
+    ) {
+        let _ = (wgpu, frame_info, local_frame_info, pass);
+    }
+}
+
+pub trait VisibleObjectClone {
+    fn clone_object(&self) -> Box<dyn VisibleObject>;
+}
+
+impl<T: 'static + VisibleObject + Clone> VisibleObjectClone for T {
+    fn clone_object(&self) -> Box<dyn VisibleObject> {
+        Box::new(self.clone())
+    }
+}
+
+impl Clone for Box<dyn VisibleObject> {
+    fn clone(&self) -> Self {
+        self.clone_object()
+    }
 }
+
+static VISIBLE_OBJECTS: LazyLock<RwLock<TypeRegistry<dyn VisibleObject>>> =
+    LazyLock::new(|| RwLock::new(TypeRegistry::new()));
+
+/// Make `T` (de)serializable as a `Box<dyn VisibleObject>`, `tag` has to be unique
+pub fn register_visible_object<T: VisibleObject + Serialize + DeserializeOwned>(tag: &'static str) {
+    VISIBLE_OBJECTS
+        .write()
+        .unwrap()
+        .register::<T>(tag, |object| Box::new(object));
+}
+
+crate::impl_registry_serde!(VisibleObject, VISIBLE_OBJECTS);
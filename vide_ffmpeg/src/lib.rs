@@ -1,7 +1,8 @@
 use std::{
-    fs::File,
+    fs::{self, File},
     io::Write,
     path::{Path, PathBuf},
+    process::Command,
     sync::mpsc::channel,
 };
 
@@ -17,6 +18,7 @@ use ac_ffmpeg::{
     time::{TimeBase, Timestamp},
 };
 use vide_common::{config::RenderConfiguration, prelude::TimeCode, render::Wgpu, FrameInfo};
+use vide_project::marker::{write_chapter_sidecars, Chapter};
 use vide_render::{interface::OutputHandler, texture_factory::FactoryTexture};
 
 fn open_output(
@@ -53,10 +55,44 @@ struct ConfiguredProperties {
     muxer: Muxer<File>,
 }
 
+/// Chapters in FFmpeg's metadata format, times are in [`TimeCode`] units
+fn ffmetadata(chapters: &[Chapter]) -> String {
+    let escape = |text: &str| {
+        text.chars()
+            .flat_map(|c| match c {
+                '=' | ';' | '#' | '\\' | '\n' => vec!['\\', c],
+                c => vec![c],
+            })
+            .collect::<String>()
+    };
+
+    let mut metadata = ";FFMETADATA1\n".to_string();
+
+    for chapter in chapters {
+        metadata += &format!(
+            "\n[CHAPTER]\nTIMEBASE=1/{}\nSTART={}\nEND={}\ntitle={}\n",
+            TimeCode::time_base(),
+            chapter.start.value(),
+            chapter.end.value(),
+            escape(&chapter.title)
+        );
+    }
+
+    metadata
+}
+
+/// Encodes the render into a video file. Chapters of the project are written into
+/// `.chapters.vtt` and `.chapters.json` files next to the video.
+///
+/// The muxer can't write chapters into the container itself. With
+/// [`MediaExporter::remux_chapters`] they are added afterwards by running the `ffmpeg`
+/// command from `PATH`, which then has to be installed.
 pub struct MediaExporter {
     path: PathBuf,
     time_base: TimeBase,
     configured: Option<ConfiguredProperties>,
+    chapters: Vec<Chapter>,
+    remux_chapters: bool,
 }
 
 impl MediaExporter {
@@ -65,11 +101,66 @@ impl MediaExporter {
             path: path.as_ref().into(),
             time_base: TimeBase::new(1, TimeCode::time_base() as _),
             configured: None,
+            chapters: Vec::new(),
+            remux_chapters: false,
+        }
+    }
+
+    /// Also add the chapters to the video itself by remuxing it with the external
+    /// `ffmpeg` command, replacing the output file
+    pub fn remux_chapters(mut self) -> Self {
+        self.remux_chapters = true;
+        self
+    }
+
+    /// Add the chapters to the finished video by remuxing it with the `ffmpeg` command,
+    /// as the library doesn't support writing chapters
+    fn embed_chapters(&self) {
+        let metadata_path = self.path.with_extension("ffmetadata");
+        let remuxed_path = self.path.with_extension(format!(
+            "chapters.{}",
+            self.path
+                .extension()
+                .and_then(|extension| extension.to_str())
+                .unwrap_or_default()
+        ));
+
+        if let Err(error) = fs::write(&metadata_path, ffmetadata(&self.chapters)) {
+            log::error!("Unable to write chapter metadata: {error}");
+            return;
+        }
+
+        let status = Command::new("ffmpeg")
+            .args(["-y", "-loglevel", "error", "-i"])
+            .arg(&self.path)
+            .arg("-i")
+            .arg(&metadata_path)
+            .args(["-map", "0", "-map_chapters", "1", "-codec", "copy"])
+            .arg(&remuxed_path)
+            .status();
+
+        match status {
+            Ok(status) if status.success() => {
+                if let Err(error) = fs::rename(&remuxed_path, &self.path) {
+                    log::error!("Unable to replace video with the one with chapters: {error}");
+                }
+            }
+            Ok(status) => log::error!("Adding chapters with ffmpeg failed ({status})"),
+            Err(error) => log::error!(
+                "Chapters are only written to the sidecar files, the ffmpeg command is unavailable: {error}"
+            ),
         }
+
+        let _ = fs::remove_file(&metadata_path);
+        let _ = fs::remove_file(&remuxed_path);
     }
 }
 
 impl OutputHandler for MediaExporter {
+    fn set_chapters(&mut self, chapters: &[Chapter]) {
+        self.chapters = chapters.to_vec();
+    }
+
     fn configure(&mut self, wgpu: &Wgpu, config: &RenderConfiguration) -> wgpu::TextureFormat {
         let width = config.resolution.0 as u32;
         let height = config.resolution.1 as u32;
@@ -215,10 +306,10 @@ impl OutputHandler for MediaExporter {
 
     fn finish(&mut self, _wgpu: &Wgpu) {
         let ConfiguredProperties {
-            ref mut encoder,
-            ref mut muxer,
+            mut encoder,
+            mut muxer,
             ..
-        } = self.configured.as_mut().expect("Not configured yet");
+        } = self.configured.take().expect("Not configured yet");
 
         encoder.flush().unwrap();
 
@@ -227,5 +318,22 @@ impl OutputHandler for MediaExporter {
         }
 
         muxer.flush().unwrap();
+
+        // Writes the trailer, so the file is complete
+        muxer.close().unwrap();
+
+        if self.chapters.is_empty() {
+            return;
+        }
+
+        log::info!("Writing {} chapters", self.chapters.len());
+
+        if let Err(error) = write_chapter_sidecars(&self.path, &self.chapters) {
+            log::error!("Unable to write chapter sidecar files: {error}");
+        }
+
+        if self.remux_chapters {
+            self.embed_chapters();
+        }
     }
 }
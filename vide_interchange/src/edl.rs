@@ -0,0 +1,421 @@
+//! CMX3600 edit decision lists. Source timecodes become content offsets as they are,
+//! footage that doesn't start at timecode `00:00:00:00` has to be slipped by its start
+//! (see [`Project::slip_clip`]). Record timecodes are made relative to the first event.
+//! `LOC` comments become markers.
+
+use std::collections::HashMap;
+
+use vide_common::{
+    prelude::TimeCode, time_code::UnboundedTimecodeRange, visible_object::VisibleObject,
+};
+use vide_project::{
+    clip::{Clip, ClipId},
+    marker::Marker,
+    time_remap::TimeRemap,
+    track::{Track, TrackId, TrackKind},
+    transition::Transition,
+    Project,
+};
+
+use crate::{smpte::TimecodeRate, Imported, MediaReference};
+
+#[derive(Debug)]
+struct Event {
+    line: usize,
+    reel: String,
+    channels: String,
+    transition: String,
+    /// Transition duration in frames
+    duration: i64,
+    source: (i64, i64),
+    record: (i64, i64),
+    clip_name: Option<String>,
+    source_file: Option<String>,
+    /// Frames per second from an `M2` speed change
+    speed: Option<f64>,
+}
+
+/// Read a CMX3600 EDL. `frames_per_second` is the rate of the timecodes, the `FCM`
+/// line decides if they're drop frame. `resolve_media` is called with the reel name
+/// (and the source file if the EDL has one) to create the footage of every clip.
+pub fn import(
+    edl: &str,
+    frames_per_second: f64,
+    mut resolve_media: impl FnMut(&MediaReference) -> Option<Box<dyn VisibleObject>>,
+) -> Imported {
+    let mut warnings = Vec::new();
+    let mut rate = TimecodeRate::new(frames_per_second, false);
+    let mut events: Vec<Event> = Vec::new();
+    // Record frame and name of `LOC` comments
+    let mut locators: Vec<(i64, String)> = Vec::new();
+
+    for (index, line) in edl.lines().enumerate() {
+        let line_number = index + 1;
+        let line = line.trim();
+
+        if line.is_empty() || line.starts_with("TITLE:") {
+            continue;
+        }
+
+        if let Some(mode) = line.strip_prefix("FCM:") {
+            rate.drop_frame = mode.trim() == "DROP FRAME";
+            continue;
+        }
+
+        if let Some(comment) = line.strip_prefix('*') {
+            let comment = comment.trim();
+
+            // `* LOC: 01:00:05:00 RED     Name`, at a record timecode
+            if let Some(locator) = comment.strip_prefix("LOC:") {
+                let mut parts = locator.split_whitespace();
+                match parts
+                    .next()
+                    .and_then(|timecode| rate.parse_frames(timecode))
+                {
+                    Some(frame) => {
+                        let name = parts.skip(1).collect::<Vec<_>>().join(" ");
+                        locators.push((frame, name));
+                    }
+                    None => warnings.push(format!("Line {line_number}: invalid locator")),
+                }
+                continue;
+            }
+
+            let Some(event) = events.last_mut() else {
+                continue;
+            };
+
+            if let Some(name) = comment.strip_prefix("FROM CLIP NAME:") {
+                event.clip_name = Some(name.trim().to_string());
+            } else if let Some(file) = comment.strip_prefix("SOURCE FILE:") {
+                event.source_file = Some(file.trim().to_string());
+            } else if let Some(effect) = comment.strip_prefix("EFFECT NAME:") {
+                warnings.push(format!(
+                    "Line {line_number}: effect \"{}\" is not supported",
+                    effect.trim()
+                ));
+            }
+            continue;
+        }
+
+        let tokens = line.split_whitespace().collect::<Vec<_>>();
+
+        if tokens[0] == "M2" {
+            match (
+                tokens.get(2).and_then(|speed| speed.parse::<f64>().ok()),
+                events.last_mut(),
+            ) {
+                (Some(speed), Some(event)) => event.speed = Some(speed),
+                _ => warnings.push(format!("Line {line_number}: invalid speed change")),
+            }
+            continue;
+        }
+
+        if !tokens[0].chars().all(|c| c.is_ascii_digit()) {
+            warnings.push(format!("Line {line_number}: skipped unsupported line"));
+            continue;
+        }
+
+        match parse_event(&tokens, line_number, rate) {
+            Some(event) => {
+                if !matches!(event.transition.as_str(), "C" | "D") {
+                    warnings.push(format!(
+                        "Line {line_number}: transition \"{}\" is not supported, imported as a cut",
+                        event.transition
+                    ));
+                }
+                events.push(event);
+            }
+            None => warnings.push(format!("Line {line_number}: skipped invalid event")),
+        }
+    }
+
+    let origin = events
+        .iter()
+        .map(|event| event.record.0)
+        .min()
+        .unwrap_or_default();
+
+    let mut project = Project::new();
+    let mut tracks: HashMap<&'static str, TrackId> = HashMap::new();
+
+    for event in &events {
+        for track_name in track_names(&event.channels) {
+            let track_id = *tracks.entry(*track_name).or_insert_with(|| {
+                let kind = if track_name.starts_with('V') {
+                    TrackKind::Video
+                } else {
+                    TrackKind::Audio
+                };
+                project.add_track(Track::new(*track_name, kind))
+            });
+            let track = project.track_mut(track_id).expect("track was just added");
+
+            import_event(
+                event,
+                track,
+                origin,
+                rate,
+                &mut resolve_media,
+                &mut warnings,
+            );
+        }
+    }
+
+    for (frame, name) in locators {
+        project.add_marker(Marker::new(name, rate.time_code(frame - origin)));
+    }
+
+    Imported { project, warnings }
+}
+
+fn parse_event(tokens: &[&str], line: usize, rate: TimecodeRate) -> Option<Event> {
+    if tokens.len() < 8 {
+        return None;
+    }
+
+    let timecodes = tokens[tokens.len() - 4..]
+        .iter()
+        .map(|timecode| rate.parse_frames(timecode))
+        .collect::<Option<Vec<_>>>()?;
+
+    let transition = tokens[3].to_string();
+    let duration = if tokens.len() > 8 {
+        tokens[4].parse().ok()?
+    } else {
+        0
+    };
+
+    Some(Event {
+        line,
+        reel: tokens[1].to_string(),
+        channels: tokens[2].to_string(),
+        transition,
+        duration,
+        source: (timecodes[0], timecodes[1]),
+        record: (timecodes[2], timecodes[3]),
+        clip_name: None,
+        source_file: None,
+        speed: None,
+    })
+}
+
+/// The tracks an event with these channels goes on
+fn track_names(channels: &str) -> &'static [&'static str] {
+    match channels {
+        "V" => &["V1"],
+        "A" | "A1" => &["A1"],
+        "A2" => &["A2"],
+        "AA" => &["A1", "A2"],
+        "B" | "A/V" | "AA/V" => &["V1", "A1"],
+        _ => &["V1"],
+    }
+}
+
+fn import_event(
+    event: &Event,
+    track: &mut Track,
+    origin: i64,
+    rate: TimecodeRate,
+    resolve_media: &mut impl FnMut(&MediaReference) -> Option<Box<dyn VisibleObject>>,
+    warnings: &mut Vec<String>,
+) {
+    let line = event.line;
+
+    // Black and zero length events (like the outgoing side of a dissolve) only
+    // take up time
+    if event.reel == "BL" || event.record.0 == event.record.1 {
+        return;
+    }
+
+    let mut start = rate.time_code(event.record.0 - origin);
+    let end = rate.time_code(event.record.1 - origin);
+    let mut content_offset = rate.time_code(event.source.0);
+
+    let mut transition = None;
+    match event.transition.as_str() {
+        "C" => {}
+        "D" => {
+            // CMX dissolves start at the record in, transitions are centered on the cut
+            let duration = rate.time_code(event.duration);
+            let half = TimeCode::new(duration.value() / 2);
+
+            match previous_clip(track, start) {
+                Some(from) => {
+                    let from_clip = track
+                        .clips_mut()
+                        .iter_mut()
+                        .find(|clip| clip.id() == from)
+                        .expect("previous clip is on this track");
+                    let from_range = from_clip.range();
+                    from_clip.set_range(UnboundedTimecodeRange::new(
+                        from_range.start(),
+                        Some(start + half),
+                    ));
+
+                    start = start + half;
+                    content_offset = content_offset + half;
+                    transition = Some((from, duration));
+                }
+                None => warnings.push(format!(
+                    "Line {line}: dissolve doesn't follow a clip, imported as a cut"
+                )),
+            }
+        }
+        _ => {}
+    }
+
+    let mut clip = Clip::new(UnboundedTimecodeRange::new(Some(start), Some(end)));
+    clip.set_name(event.clip_name.as_deref().unwrap_or(&event.reel));
+    clip.set_content_offset(content_offset);
+
+    if let Some(speed) = event.speed {
+        let speed = speed / rate.frames_per_second;
+
+        if speed == 0.0 {
+            clip.set_time_remap(TimeRemap::frozen_at(content_offset));
+        } else if speed > 0.0 {
+            // The speed also applies to the content offset
+            clip.set_content_offset(TimeCode::new(
+                (content_offset.value() as f64 / speed) as i64,
+            ));
+            clip.set_time_remap(TimeRemap::with_speed(speed));
+        } else {
+            warnings.push(format!(
+                "Line {line}: reverse speed changes are not supported"
+            ));
+        }
+    }
+
+    if track.kind() == TrackKind::Video {
+        let media = MediaReference {
+            name: event.reel.clone(),
+            target_url: event.source_file.clone(),
+            available_range: None,
+        };
+
+        match resolve_media(&media) {
+            Some(video) => clip.set_video(Some(video)),
+            None => warnings.push(format!(
+                "Line {line}: no footage for reel \"{}\"",
+                event.reel
+            )),
+        }
+    }
+
+    if let Some((from, duration)) = transition {
+        track.add_transition(Transition::crossfade(from, clip.id(), duration));
+    }
+
+    track.add_clip(clip);
+}
+
+/// The clip on `track` that ends at `time`
+fn previous_clip(track: &Track, time: TimeCode) -> Option<ClipId> {
+    track
+        .clips()
+        .iter()
+        .find(|clip| clip.range().end() == Some(time))
+        .map(Clip::id)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    const EDL: &str = "TITLE: TEST
+FCM: NON-DROP FRAME
+
+001  A001     V     C        01:00:00:00 01:00:04:00 01:00:00:00 01:00:04:00
+* FROM CLIP NAME: first
+002  A001     V     C        01:00:04:00 01:00:04:00 01:00:04:00 01:00:04:00
+002  B001     V     D    030 02:00:00:00 02:00:05:00 01:00:04:00 01:00:09:00
+* FROM CLIP NAME: second
+003  C001     V     C        00:00:01:00 00:00:03:00 01:00:09:00 01:00:11:00
+* FROM CLIP NAME: fast
+M2   C001       050.0                00:00:01:00
+* LOC: 01:00:02:00 RED     Marker
+";
+
+    #[test]
+    fn dissolve_and_speed_change() {
+        let mut reels = Vec::new();
+        let imported = import(EDL, 25.0, |media| {
+            reels.push(media.name.clone());
+            None
+        });
+
+        assert_eq!(reels, ["A001", "B001", "C001"]);
+        assert!(imported
+            .warnings
+            .iter()
+            .all(|warning| warning.contains("no footage")));
+
+        let track = &imported.project.tracks()[0];
+        let clips = track.clips();
+        assert_eq!(track.name(), "V1");
+        assert_eq!(clips.len(), 3);
+
+        // The dissolve starts at the record in of the incoming event, and is centered
+        // on the cut after importing
+        let first = &clips[0];
+        assert_eq!(first.name(), "first");
+        assert_eq!(first.range().start(), Some(TimeCode::from_seconds(0.0)));
+        assert_eq!(first.range().end(), Some(TimeCode::from_seconds(4.6)));
+
+        let second = &clips[1];
+        assert_eq!(second.name(), "second");
+        assert_eq!(second.range().start(), Some(TimeCode::from_seconds(4.6)));
+        assert_eq!(second.range().end(), Some(TimeCode::from_seconds(9.0)));
+        assert_eq!(second.content_offset(), TimeCode::from_seconds(7200.6));
+
+        assert_eq!(
+            track.transitions(),
+            [Transition::crossfade(
+                first.id(),
+                second.id(),
+                TimeCode::from_seconds(1.2)
+            )]
+        );
+
+        // 50 frames per second on 25 fps footage
+        let fast = &clips[2];
+        assert_eq!(fast.name(), "fast");
+        assert_eq!(fast.range().start(), Some(TimeCode::from_seconds(9.0)));
+        assert_eq!(fast.range().end(), Some(TimeCode::from_seconds(11.0)));
+        assert_eq!(fast.time_remap().speed, 2.0);
+        assert_eq!(fast.content_offset(), TimeCode::from_seconds(0.5));
+        assert_eq!(
+            fast.content_time(TimeCode::default()),
+            TimeCode::from_seconds(1.0)
+        );
+
+        let markers = imported.project.markers();
+        assert_eq!(markers.len(), 1);
+        assert_eq!(markers[0].name, "Marker");
+        assert_eq!(markers[0].time, TimeCode::from_seconds(2.0));
+    }
+
+    #[test]
+    fn drop_frame_timecodes() {
+        let edl = "FCM: DROP FRAME
+001  A001     V     C        00:00:00;00 00:01:00;02 00:09:00;02 00:10:00;02
+002  A001     V     C        00:01:00;02 00:02:00;00 00:10:00;02 00:11:00;00
+";
+        let imported = import(edl, 29.97, |_| None);
+        let clips = imported.project.tracks()[0].clips();
+        let rate = TimecodeRate::new(29.97, true);
+
+        // A minute of drop frame timecode is 1800 frames, except when it skips 2
+        assert_eq!(clips.len(), 1);
+        assert_eq!(clips[0].range().start(), Some(TimeCode::default()));
+        assert_eq!(clips[0].range().end(), Some(rate.time_code(1800)));
+
+        // `00:02:00;00` doesn't exist in drop frame timecode
+        assert!(imported
+            .warnings
+            .iter()
+            .any(|warning| warning == "Line 3: skipped invalid event"));
+    }
+}
@@ -0,0 +1,480 @@
+//! Final Cut Pro XML (`.fcpxml`). The primary storyline becomes a track, connected
+//! clips go on a track per lane.
+
+use std::collections::HashMap;
+
+use roxmltree::{Document, Node, ParsingOptions};
+use vide_common::{
+    prelude::TimeCode, time_code::UnboundedTimecodeRange, visible_object::VisibleObject,
+};
+use vide_project::{
+    clip::{Clip, ClipId},
+    marker::Marker,
+    track::{Track, TrackId, TrackKind},
+    transition::Transition,
+    Project,
+};
+
+use crate::{Imported, MediaReference};
+
+/// A rational time like `1001/30000s`
+fn parse_time(text: &str) -> Option<TimeCode> {
+    let text = text.strip_suffix('s')?;
+
+    let (numerator, denominator) = match text.split_once('/') {
+        Some((numerator, denominator)) => (
+            numerator.parse::<i64>().ok()?,
+            denominator.parse::<i64>().ok()?,
+        ),
+        None => (text.parse::<i64>().ok()?, 1),
+    };
+
+    if denominator == 0 {
+        return None;
+    }
+
+    // Exact as long as the denominator divides the time base, which it does for
+    // all common frame rates
+    Some(TimeCode::new(
+        ((numerator as i128 * TimeCode::time_base() as i128) as f64 / denominator as f64).round()
+            as i64,
+    ))
+}
+
+fn time_attribute(node: Node, name: &str) -> TimeCode {
+    node.attribute(name)
+        .and_then(parse_time)
+        .unwrap_or_default()
+}
+
+struct Asset {
+    media: MediaReference,
+    start: TimeCode,
+    has_video: bool,
+}
+
+struct Importer<'a, F> {
+    assets: HashMap<&'a str, Asset>,
+    resolve_media: F,
+    project: Project,
+    tracks: HashMap<(i32, TrackKind), TrackId>,
+    warnings: Vec<String>,
+}
+
+/// Read the first project in an FCPXML document. `resolve_media` is called for every
+/// asset a clip uses to create its footage.
+pub fn import(
+    fcpxml: &str,
+    resolve_media: impl FnMut(&MediaReference) -> Option<Box<dyn VisibleObject>>,
+) -> Result<Imported, roxmltree::Error> {
+    let document = Document::parse_with_options(
+        fcpxml,
+        ParsingOptions {
+            allow_dtd: true,
+            ..Default::default()
+        },
+    )?;
+    let root = document.root_element();
+
+    let mut importer = Importer {
+        assets: HashMap::new(),
+        resolve_media,
+        project: Project::new(),
+        tracks: HashMap::new(),
+        warnings: Vec::new(),
+    };
+
+    for asset in root.descendants().filter(|node| node.has_tag_name("asset")) {
+        let Some(id) = asset.attribute("id") else {
+            continue;
+        };
+
+        // Newer versions have a `media-rep` child instead of `src`
+        let src = asset.attribute("src").or_else(|| {
+            asset
+                .children()
+                .find(|node| node.has_tag_name("media-rep"))
+                .and_then(|node| node.attribute("src"))
+        });
+
+        let start = time_attribute(asset, "start");
+        let available_range = asset
+            .attribute("duration")
+            .and_then(parse_time)
+            .map(|duration| UnboundedTimecodeRange::new(Some(start), Some(start + duration)));
+
+        importer.assets.insert(
+            id,
+            Asset {
+                media: MediaReference {
+                    name: asset.attribute("name").unwrap_or(id).to_string(),
+                    target_url: src.map(str::to_string),
+                    available_range,
+                },
+                start,
+                has_video: asset.attribute("hasVideo") != Some("0"),
+            },
+        );
+    }
+
+    let Some(sequence) = root
+        .descendants()
+        .find(|node| node.has_tag_name("sequence"))
+    else {
+        importer.warnings.push("No sequence found".to_string());
+        return Ok(importer.finish());
+    };
+
+    let Some(spine) = sequence.children().find(|node| node.has_tag_name("spine")) else {
+        importer.warnings.push("Sequence has no spine".to_string());
+        return Ok(importer.finish());
+    };
+
+    importer.import_spine(
+        spine,
+        time_attribute(sequence, "tcStart"),
+        TimeCode::default(),
+        0,
+    );
+
+    Ok(importer.finish())
+}
+
+impl<'a, F: FnMut(&MediaReference) -> Option<Box<dyn VisibleObject>>> Importer<'a, F> {
+    fn finish(self) -> Imported {
+        Imported {
+            project: self.project,
+            warnings: self.warnings,
+        }
+    }
+
+    fn track(&mut self, lane: i32, kind: TrackKind) -> &mut Track {
+        let project = &mut self.project;
+        let id = *self.tracks.entry((lane, kind)).or_insert_with(|| {
+            let name = match (lane, kind) {
+                (0, TrackKind::Video) => "Primary".to_string(),
+                (0, TrackKind::Audio) => "Primary Audio".to_string(),
+                (lane, TrackKind::Video) => format!("Lane {lane}"),
+                (lane, TrackKind::Audio) => format!("Audio Lane {lane}"),
+            };
+
+            let mut track = Track::new(name, kind);
+            track.set_z_index(lane);
+            project.add_track(track)
+        });
+
+        self.project.track_mut(id).expect("track was added")
+    }
+
+    /// Import the items of a spine. `local_start` is the time in the spine's parent
+    /// that `parent_offset` (in the project) corresponds to.
+    fn import_spine(
+        &mut self,
+        spine: Node<'a, '_>,
+        local_start: TimeCode,
+        parent_offset: TimeCode,
+        lane: i32,
+    ) {
+        let mut previous: Option<(ClipId, TrackKind)> = None;
+        let mut pending_transition: Option<(Node, TimeCode)> = None;
+
+        for item in spine.children().filter(Node::is_element) {
+            let offset = parent_offset + time_attribute(item, "offset") - local_start;
+
+            match item.tag_name().name() {
+                "transition" => {
+                    pending_transition = Some((item, offset));
+                }
+                "gap" => {
+                    self.import_connected(item, offset);
+                    previous = None;
+                }
+                "asset-clip" | "clip" | "video" => {
+                    let Some((clip, kind)) = self.import_clip(item, offset) else {
+                        previous = None;
+                        continue;
+                    };
+                    let id = clip.id();
+
+                    if let Some((transition, transition_offset)) = pending_transition.take() {
+                        self.import_transition(
+                            transition,
+                            transition_offset,
+                            previous,
+                            &clip,
+                            lane,
+                            kind,
+                        );
+                    }
+
+                    self.import_connected(item, offset);
+                    self.track(lane, kind).add_clip(clip);
+                    previous = Some((id, kind));
+                }
+                name => {
+                    self.warnings.push(format!(
+                        "Skipped unsupported {name} \"{}\"",
+                        item.attribute("name").unwrap_or_default()
+                    ));
+                    previous = None;
+                }
+            }
+        }
+
+        if let Some((transition, _)) = pending_transition {
+            self.warnings.push(format!(
+                "Transition \"{}\" has no clip to transition to",
+                transition.attribute("name").unwrap_or_default()
+            ));
+        }
+    }
+
+    /// Import the clips connected to `item`, which starts at `offset` in the project
+    fn import_connected(&mut self, item: Node<'a, '_>, offset: TimeCode) {
+        let local_start = time_attribute(item, "start");
+
+        for child in item.children().filter(Node::is_element) {
+            if let Some(lane) = child.attribute("lane").and_then(|lane| lane.parse().ok()) {
+                if child.has_tag_name("spine") {
+                    self.import_spine(
+                        child,
+                        TimeCode::default(),
+                        offset + time_attribute(child, "offset") - local_start,
+                        lane,
+                    );
+                } else if let Some((clip, kind)) = self.import_clip(
+                    child,
+                    offset + time_attribute(child, "offset") - local_start,
+                ) {
+                    self.track(lane, kind).add_clip(clip);
+                }
+            }
+        }
+    }
+
+    fn import_clip(&mut self, item: Node<'a, '_>, offset: TimeCode) -> Option<(Clip, TrackKind)> {
+        let name = item.attribute("name").unwrap_or_default();
+        let Some(duration) = item.attribute("duration").and_then(parse_time) else {
+            self.warnings
+                .push(format!("Skipped clip \"{name}\", it has no duration"));
+            return None;
+        };
+
+        // `clip` elements wrap the `video` that refers to the asset
+        let (media, media_offset) = match item.tag_name().name() {
+            "clip" => {
+                let Some(video) = item
+                    .children()
+                    .find(|node| node.has_tag_name("video") || node.has_tag_name("asset-clip"))
+                else {
+                    self.warnings
+                        .push(format!("Skipped clip \"{name}\", it has no video"));
+                    return None;
+                };
+                (
+                    video,
+                    time_attribute(item, "start") - time_attribute(video, "offset"),
+                )
+            }
+            _ => (item, TimeCode::default()),
+        };
+
+        let Some(asset) = media.attribute("ref").and_then(|id| self.assets.get(id)) else {
+            self.warnings
+                .push(format!("Skipped clip \"{name}\", its asset is missing"));
+            return None;
+        };
+
+        let kind = if asset.has_video {
+            TrackKind::Video
+        } else {
+            TrackKind::Audio
+        };
+
+        let mut clip = Clip::new(UnboundedTimecodeRange::new(
+            Some(offset),
+            Some(offset + duration),
+        ));
+        clip.set_name(name);
+        clip.set_content_offset(media_offset + time_attribute(media, "start") - asset.start);
+
+        if kind == TrackKind::Video {
+            let video = (self.resolve_media)(&asset.media);
+            match video {
+                Some(video) => clip.set_video(Some(video)),
+                None => self
+                    .warnings
+                    .push(format!("No footage for asset \"{}\"", asset.media.name)),
+            }
+        }
+
+        for child in item.children().filter(Node::is_element) {
+            let tag = child.tag_name().name();
+
+            if tag.starts_with("filter-") || tag.starts_with("adjust-") || tag == "timeMap" {
+                self.warnings.push(format!(
+                    "{tag} \"{}\" on clip \"{name}\" is not supported",
+                    child.attribute("name").unwrap_or_default()
+                ));
+            } else if tag == "marker" || tag == "chapter-marker" {
+                // Marker times are in the time of the clip, like its start
+                let time = offset + time_attribute(child, "start") - time_attribute(item, "start");
+                let name = child.attribute("value").unwrap_or_default();
+
+                // Chapter markers have a one frame duration, but last until the next one
+                let mut marker = if tag == "chapter-marker" {
+                    Marker::chapter(name, time)
+                } else {
+                    Marker::new(name, time)
+                };
+                if tag == "marker" {
+                    marker.duration = child.attribute("duration").and_then(parse_time);
+                }
+                marker.comment = child.attribute("note").unwrap_or_default().to_string();
+
+                self.project.add_marker(marker);
+            }
+        }
+
+        Some((clip, kind))
+    }
+
+    /// Add a transition between `previous` and `to`. FCP transitions overlap both clips
+    /// and are usually centered on the cut already.
+    fn import_transition(
+        &mut self,
+        transition: Node,
+        offset: TimeCode,
+        previous: Option<(ClipId, TrackKind)>,
+        to: &Clip,
+        lane: i32,
+        kind: TrackKind,
+    ) {
+        let name = transition.attribute("name").unwrap_or_default();
+        let duration = time_attribute(transition, "duration");
+
+        let Some((from, _)) = previous.filter(|(_, previous_kind)| *previous_kind == kind) else {
+            self.warnings
+                .push(format!("Transition \"{name}\" isn't between two clips"));
+            return;
+        };
+
+        let cut = to.range().start().unwrap_or_default();
+        if offset + TimeCode::new(duration.value() / 2) != cut {
+            self.warnings.push(format!(
+                "Transition \"{name}\" is not centered on the cut, it will be"
+            ));
+        }
+
+        if !name.contains("Dissolve") {
+            self.warnings
+                .push(format!("Transition \"{name}\" is imported as a crossfade"));
+        }
+
+        self.track(lane, kind)
+            .add_transition(Transition::crossfade(from, to.id(), duration));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    const FCPXML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE fcpxml>
+<fcpxml version="1.9">
+    <resources>
+        <format id="r1" frameDuration="1/25s" width="1920" height="1080"/>
+        <asset id="r2" name="A" src="file:///a.mov" start="0s" duration="10s" hasVideo="1"/>
+        <asset id="r3" name="B" start="3600s" duration="10s" hasVideo="1">
+            <media-rep kind="original-media" src="file:///b.mov"/>
+        </asset>
+    </resources>
+    <library>
+        <event name="Event">
+            <project name="Project">
+                <sequence format="r1" tcStart="3600s" duration="8s">
+                    <spine>
+                        <asset-clip ref="r2" name="first" offset="3600s" start="1s" duration="4s"/>
+                        <transition name="Cross Dissolve" offset="7207/2s" duration="1s"/>
+                        <asset-clip ref="r3" name="second" offset="3604s" start="3602s" duration="4s">
+                            <marker start="3603s" duration="1/25s" value="Marker"/>
+                        </asset-clip>
+                    </spine>
+                </sequence>
+            </project>
+        </event>
+    </library>
+</fcpxml>
+"#;
+
+    #[test]
+    fn parse_rational_time() {
+        assert_eq!(parse_time("3600s"), Some(TimeCode::from_seconds(3600.0)));
+        assert_eq!(parse_time("7207/2s"), Some(TimeCode::from_seconds(3603.5)));
+        assert_eq!(parse_time("1001/30000s"), Some(TimeCode::new(2002)));
+        assert_eq!(parse_time("1/0s"), None);
+        assert_eq!(parse_time("10"), None);
+    }
+
+    #[test]
+    fn spine_with_transition() {
+        let mut urls = Vec::new();
+        let imported = import(FCPXML, |media| {
+            urls.push(media.target_url.clone());
+            None
+        })
+        .unwrap();
+
+        assert_eq!(
+            urls,
+            [
+                Some("file:///a.mov".to_string()),
+                Some("file:///b.mov".to_string())
+            ]
+        );
+        assert!(
+            imported
+                .warnings
+                .iter()
+                .all(|warning| warning.starts_with("No footage")),
+            "{:?}",
+            imported.warnings
+        );
+
+        let tracks = imported.project.tracks();
+        assert_eq!(tracks.len(), 1);
+        assert_eq!(tracks[0].name(), "Primary");
+
+        let clips = tracks[0].clips();
+        assert_eq!(clips.len(), 2);
+
+        // Times are relative to the start of the sequence and the asset
+        let first = &clips[0];
+        assert_eq!(first.name(), "first");
+        assert_eq!(first.range().start(), Some(TimeCode::from_seconds(0.0)));
+        assert_eq!(first.range().end(), Some(TimeCode::from_seconds(4.0)));
+        assert_eq!(first.content_offset(), TimeCode::from_seconds(1.0));
+
+        let second = &clips[1];
+        assert_eq!(second.name(), "second");
+        assert_eq!(second.range().start(), Some(TimeCode::from_seconds(4.0)));
+        assert_eq!(second.range().end(), Some(TimeCode::from_seconds(8.0)));
+        assert_eq!(second.content_offset(), TimeCode::from_seconds(2.0));
+
+        assert_eq!(
+            tracks[0].transitions(),
+            [Transition::crossfade(
+                first.id(),
+                second.id(),
+                TimeCode::from_seconds(1.0)
+            )]
+        );
+
+        let markers = imported.project.markers();
+        assert_eq!(markers.len(), 1);
+        assert_eq!(markers[0].time, TimeCode::from_seconds(5.0));
+        assert_eq!(markers[0].duration, Some(TimeCode::from_seconds(0.04)));
+    }
+}
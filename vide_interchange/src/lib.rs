@@ -0,0 +1,32 @@
+//! Conversion between projects and the timeline formats of other editing software
+
+use vide_common::{prelude::TimeCode, time_code::UnboundedTimecodeRange};
+use vide_project::Project;
+
+pub mod edl;
+pub mod fcpxml;
+pub mod otio;
+pub mod smpte;
+
+/// The media an imported clip refers to, used to look up the footage for it
+#[derive(Debug, Clone)]
+pub struct MediaReference {
+    /// The name of the clip, or the reel / source name if the format has no paths
+    pub name: String,
+    /// Path or url of the media file
+    pub target_url: Option<String>,
+    /// The part of the media that exists, in the media's own time
+    pub available_range: Option<UnboundedTimecodeRange>,
+}
+
+/// An imported project, anything that couldn't be converted is listed in `warnings`
+#[derive(Debug)]
+pub struct Imported {
+    pub project: Project,
+    pub warnings: Vec<String>,
+}
+
+/// The time code closest to `value` frames at `rate` frames per second
+pub(crate) fn time_code(value: f64, rate: f64) -> TimeCode {
+    TimeCode::new((value / rate * TimeCode::time_base() as f64).round() as i64)
+}
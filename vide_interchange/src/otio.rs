@@ -0,0 +1,896 @@
+//! [OpenTimelineIO](https://opentimeline.io) `.otio` files. Only the edit is converted:
+//! tracks, clips with their ranges, gaps, transitions and markers.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use vide_common::{
+    prelude::TimeCode, time_code::UnboundedTimecodeRange, types::FramesPerSecond,
+    visible_object::VisibleObject,
+};
+use vide_project::{
+    clip::{Clip, ClipId},
+    marker::{Marker, MarkerKind},
+    time_remap::TimeRemap,
+    track::{Track, TrackKind},
+    transition::{Transition, TransitionKind},
+    Project,
+};
+
+use crate::{time_code, Imported, MediaReference};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(tag = "OTIO_SCHEMA", rename = "RationalTime.1")]
+struct RationalTime {
+    rate: f64,
+    value: f64,
+}
+
+impl RationalTime {
+    fn new(time_code: TimeCode, rate: f64) -> Self {
+        Self {
+            rate,
+            value: (time_code.seconds() * rate).round(),
+        }
+    }
+
+    fn time_code(&self) -> TimeCode {
+        time_code(self.value, self.rate)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(tag = "OTIO_SCHEMA", rename = "TimeRange.1")]
+struct TimeRange {
+    start_time: RationalTime,
+    duration: RationalTime,
+}
+
+impl TimeRange {
+    fn new(start: TimeCode, duration: TimeCode, rate: f64) -> Self {
+        Self {
+            start_time: RationalTime::new(start, rate),
+            duration: RationalTime::new(duration, rate),
+        }
+    }
+
+    fn range(&self) -> UnboundedTimecodeRange {
+        let start = self.start_time.time_code();
+        UnboundedTimecodeRange::new(Some(start), Some(start + self.duration.time_code()))
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "OTIO_SCHEMA", rename = "Marker.2")]
+struct OtioMarker {
+    #[serde(default)]
+    name: String,
+    marked_range: TimeRange,
+    #[serde(default)]
+    color: String,
+    #[serde(default)]
+    comment: String,
+    #[serde(default)]
+    metadata: Value,
+}
+
+impl OtioMarker {
+    fn new(marker: &Marker, rate: f64) -> Self {
+        let (color, metadata) = match marker.kind {
+            MarkerKind::Marker => ("RED", Value::Null),
+            MarkerKind::Chapter => ("GREEN", serde_json::json!({ "vide": { "chapter": true } })),
+        };
+
+        Self {
+            name: marker.name.clone(),
+            marked_range: TimeRange::new(marker.time, marker.duration.unwrap_or_default(), rate),
+            color: color.to_string(),
+            comment: marker.comment.clone(),
+            metadata,
+        }
+    }
+
+    /// `offset` is the project time of the start of the marked item's time
+    fn marker(&self, offset: TimeCode) -> Marker {
+        let duration = self.marked_range.duration.time_code();
+
+        Marker {
+            name: self.name.clone(),
+            kind: if self.metadata["vide"]["chapter"] == true {
+                MarkerKind::Chapter
+            } else {
+                MarkerKind::Marker
+            },
+            time: offset + self.marked_range.start_time.time_code(),
+            duration: (duration > TimeCode::default()).then_some(duration),
+            comment: self.comment.clone(),
+        }
+    }
+}
+
+/// Add the markers of an item to the project, `offset` is the project time of the
+/// start of the item's time
+fn import_markers(
+    markers: &[Value],
+    offset: TimeCode,
+    project: &mut Project,
+    warnings: &mut Vec<String>,
+) {
+    for marker in markers {
+        match OtioMarker::deserialize(marker) {
+            Ok(marker) => project.add_marker(marker.marker(offset)),
+            Err(error) => warnings.push(format!("Skipped invalid marker: {error}")),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "OTIO_SCHEMA")]
+enum OtioMediaReference {
+    #[serde(rename = "ExternalReference.1")]
+    External {
+        target_url: String,
+        #[serde(default)]
+        available_range: Option<TimeRange>,
+    },
+    #[serde(rename = "MissingReference.1")]
+    Missing {},
+    #[serde(other)]
+    Unsupported,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct OtioClip {
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    source_range: Option<TimeRange>,
+    /// Used by `Clip.1`
+    #[serde(default)]
+    media_reference: Option<OtioMediaReference>,
+    /// Used by `Clip.2`
+    #[serde(default, skip_serializing)]
+    media_references: HashMap<String, OtioMediaReference>,
+    #[serde(default, skip_serializing)]
+    active_media_reference_key: Option<String>,
+    #[serde(default)]
+    effects: Vec<Value>,
+    #[serde(default)]
+    markers: Vec<Value>,
+    #[serde(default = "enabled")]
+    enabled: bool,
+}
+
+impl OtioClip {
+    fn take_media_reference(&mut self) -> Option<OtioMediaReference> {
+        self.media_reference.take().or_else(|| {
+            let key = self
+                .active_media_reference_key
+                .as_deref()
+                .unwrap_or("DEFAULT_MEDIA");
+            self.media_references.remove(key)
+        })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct OtioTransition {
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    transition_type: String,
+    in_offset: RationalTime,
+    out_offset: RationalTime,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "OTIO_SCHEMA")]
+enum Item {
+    #[serde(rename = "Clip.1", alias = "Clip.2")]
+    Clip(OtioClip),
+    #[serde(rename = "Gap.1")]
+    Gap { source_range: TimeRange },
+    #[serde(rename = "Transition.1")]
+    Transition(OtioTransition),
+    #[serde(other)]
+    Unsupported,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct OtioTrack {
+    #[serde(default)]
+    name: String,
+    #[serde(default = "video_kind")]
+    kind: String,
+    #[serde(default)]
+    children: Vec<Item>,
+    #[serde(default)]
+    markers: Vec<Value>,
+    #[serde(default = "enabled")]
+    enabled: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "OTIO_SCHEMA")]
+enum StackChild {
+    #[serde(rename = "Track.1")]
+    Track(OtioTrack),
+    #[serde(other)]
+    Unsupported,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "OTIO_SCHEMA", rename = "Stack.1")]
+struct Stack {
+    #[serde(default)]
+    name: String,
+    children: Vec<StackChild>,
+    #[serde(default)]
+    markers: Vec<Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "OTIO_SCHEMA", rename = "Timeline.1")]
+struct Timeline {
+    #[serde(default)]
+    name: String,
+    tracks: Stack,
+}
+
+fn enabled() -> bool {
+    true
+}
+
+fn video_kind() -> String {
+    "Video".to_string()
+}
+
+/// Read an `.otio` timeline. `resolve_media` is called for every clip to create the
+/// footage it shows, clips it returns `None` for are kept without video.
+pub fn import(
+    otio: &str,
+    mut resolve_media: impl FnMut(&MediaReference) -> Option<Box<dyn VisibleObject>>,
+) -> serde_json::Result<Imported> {
+    let timeline: Timeline = serde_json::from_str(otio)?;
+    let mut project = Project::new();
+    let mut warnings = Vec::new();
+
+    import_markers(
+        &timeline.tracks.markers,
+        TimeCode::default(),
+        &mut project,
+        &mut warnings,
+    );
+
+    for (index, child) in timeline.tracks.children.into_iter().enumerate() {
+        let StackChild::Track(otio_track) = child else {
+            warnings.push(format!(
+                "Skipped unsupported item {index} in the top-level stack"
+            ));
+            continue;
+        };
+
+        let kind = match otio_track.kind.as_str() {
+            "Video" => TrackKind::Video,
+            "Audio" => TrackKind::Audio,
+            kind => {
+                warnings.push(format!(
+                    "Track \"{}\" has unknown kind \"{kind}\", imported as video",
+                    otio_track.name
+                ));
+                TrackKind::Video
+            }
+        };
+
+        let mut track = Track::new(otio_track.name, kind);
+        track.set_z_index(index as i32);
+        track.set_enabled(otio_track.enabled);
+
+        import_markers(
+            &otio_track.markers,
+            TimeCode::default(),
+            &mut project,
+            &mut warnings,
+        );
+
+        import_items(
+            otio_track.children,
+            &mut track,
+            &mut project,
+            &mut resolve_media,
+            &mut warnings,
+        );
+
+        project.add_track(track);
+    }
+
+    Ok(Imported { project, warnings })
+}
+
+fn import_items(
+    items: Vec<Item>,
+    track: &mut Track,
+    project: &mut Project,
+    resolve_media: &mut impl FnMut(&MediaReference) -> Option<Box<dyn VisibleObject>>,
+    warnings: &mut Vec<String>,
+) {
+    let mut time = TimeCode::default();
+    let mut previous: Option<ClipId> = None;
+    let mut pending_transition: Option<OtioTransition> = None;
+
+    for item in items {
+        match item {
+            Item::Clip(otio_clip) => {
+                let Some(clip) = import_clip(otio_clip, time, project, resolve_media, warnings)
+                else {
+                    previous = None;
+                    continue;
+                };
+
+                if let Some(transition) = pending_transition.take() {
+                    match previous {
+                        Some(from) => {
+                            track.add_transition(import_transition(
+                                transition,
+                                from,
+                                clip.id(),
+                                warnings,
+                            ));
+                        }
+                        None => warnings.push(format!(
+                            "Transition \"{}\" on track \"{}\" doesn't follow a clip",
+                            transition.name,
+                            track.name()
+                        )),
+                    }
+                }
+
+                time = clip.range().end().unwrap_or(time);
+                previous = Some(clip.id());
+                track.add_clip(clip);
+            }
+            Item::Gap { source_range } => {
+                time = time + source_range.duration.time_code();
+                previous = None;
+            }
+            Item::Transition(transition) => pending_transition = Some(transition),
+            Item::Unsupported => {
+                warnings.push(format!(
+                    "Skipped unsupported item on track \"{}\", clips after it might be too early",
+                    track.name()
+                ));
+                previous = None;
+            }
+        }
+    }
+
+    if let Some(transition) = pending_transition {
+        warnings.push(format!(
+            "Transition \"{}\" at the end of track \"{}\" has no clip to transition to",
+            transition.name,
+            track.name()
+        ));
+    }
+}
+
+fn import_clip(
+    mut otio_clip: OtioClip,
+    start: TimeCode,
+    project: &mut Project,
+    resolve_media: &mut impl FnMut(&MediaReference) -> Option<Box<dyn VisibleObject>>,
+    warnings: &mut Vec<String>,
+) -> Option<Clip> {
+    let (target_url, available_range) = match otio_clip.take_media_reference() {
+        Some(OtioMediaReference::External {
+            target_url,
+            available_range,
+        }) => (Some(target_url), available_range),
+        Some(OtioMediaReference::Unsupported) => {
+            warnings.push(format!(
+                "Clip \"{}\" has an unsupported media reference",
+                otio_clip.name
+            ));
+            (None, None)
+        }
+        Some(OtioMediaReference::Missing {}) | None => (None, None),
+    };
+
+    let Some(source_range) = otio_clip.source_range.or(available_range) else {
+        warnings.push(format!(
+            "Skipped clip \"{}\", it has no source range",
+            otio_clip.name
+        ));
+        return None;
+    };
+
+    let duration = source_range.duration.time_code();
+    let mut clip = Clip::new(UnboundedTimecodeRange::new(
+        Some(start),
+        Some(start + duration),
+    ));
+    clip.set_enabled(otio_clip.enabled);
+
+    // Footage starts at 0, media in OTIO can start anywhere (like at a timecode)
+    let media_start =
+        available_range.map_or(TimeCode::default(), |range| range.start_time.time_code());
+    let content_offset = source_range.start_time.time_code() - media_start;
+    clip.set_content_offset(content_offset);
+
+    for effect in &otio_clip.effects {
+        let schema = effect["OTIO_SCHEMA"].as_str().unwrap_or_default();
+        let time_scalar = effect["time_scalar"].as_f64().unwrap_or(1.0);
+
+        if schema.starts_with("FreezeFrame.")
+            || schema.starts_with("LinearTimeWarp.") && time_scalar == 0.0
+        {
+            clip.set_time_remap(TimeRemap::frozen_at(content_offset));
+        } else if schema.starts_with("LinearTimeWarp.") {
+            // The speed also applies to the content offset
+            clip.set_content_offset(TimeCode::new(
+                (content_offset.value() as f64 / time_scalar) as i64,
+            ));
+            clip.set_time_remap(TimeRemap::with_speed(time_scalar));
+        } else {
+            warnings.push(format!(
+                "Effect \"{}\" on clip \"{}\" is not supported",
+                effect["effect_name"].as_str().unwrap_or(schema),
+                otio_clip.name
+            ));
+        }
+    }
+
+    // Clip markers are in the time of the media
+    import_markers(
+        &otio_clip.markers,
+        start - source_range.start_time.time_code(),
+        project,
+        warnings,
+    );
+
+    let media = MediaReference {
+        name: otio_clip.name,
+        target_url,
+        available_range: available_range.map(|range| range.range()),
+    };
+
+    match resolve_media(&media) {
+        Some(video) => clip.set_video(Some(video)),
+        None => {
+            if let Some(target_url) = &media.target_url {
+                warnings.push(format!("No footage for \"{target_url}\""));
+            }
+        }
+    }
+
+    clip.set_name(media.name);
+    Some(clip)
+}
+
+fn import_transition(
+    transition: OtioTransition,
+    from: ClipId,
+    to: ClipId,
+    warnings: &mut Vec<String>,
+) -> Transition {
+    let in_offset = transition.in_offset.time_code();
+    let out_offset = transition.out_offset.time_code();
+
+    if in_offset != out_offset {
+        warnings.push(format!(
+            "Transition \"{}\" is not centered on the cut, it will be",
+            transition.name
+        ));
+    }
+
+    if transition.transition_type != "SMPTE_Dissolve" {
+        warnings.push(format!(
+            "Transition \"{}\" of type \"{}\" is imported as a crossfade",
+            transition.name, transition.transition_type
+        ));
+    }
+
+    Transition::crossfade(from, to, in_offset + out_offset)
+}
+
+/// An exported timeline, anything that couldn't be converted is listed in `warnings`
+#[derive(Debug)]
+pub struct Exported {
+    pub otio: String,
+    pub warnings: Vec<String>,
+}
+
+/// Write the edit of a project as an `.otio` timeline, with times in frames at
+/// `frames_per_second`. `media_url` returns the path of the footage a clip shows,
+/// clips without one get a missing media reference.
+pub fn export(
+    project: &Project,
+    name: &str,
+    frames_per_second: FramesPerSecond,
+    mut media_url: impl FnMut(&Clip) -> Option<String>,
+) -> Exported {
+    let mut warnings = Vec::new();
+
+    let mut tracks = project.tracks().iter().collect::<Vec<_>>();
+    tracks.sort_by_key(|track| track.z_index());
+
+    let children = tracks
+        .into_iter()
+        .map(|track| {
+            StackChild::Track(export_track(
+                track,
+                frames_per_second,
+                &mut media_url,
+                &mut warnings,
+            ))
+        })
+        .collect();
+
+    let timeline = Timeline {
+        name: name.to_string(),
+        tracks: Stack {
+            name: "tracks".to_string(),
+            children,
+            markers: project
+                .markers()
+                .iter()
+                .map(|marker| {
+                    serde_json::to_value(OtioMarker::new(marker, frames_per_second))
+                        .expect("markers are always valid JSON")
+                })
+                .collect(),
+        },
+    };
+
+    Exported {
+        otio: serde_json::to_string_pretty(&timeline)
+            .expect("OTIO timelines are always valid JSON"),
+        warnings,
+    }
+}
+
+fn export_track(
+    track: &Track,
+    rate: f64,
+    media_url: &mut impl FnMut(&Clip) -> Option<String>,
+    warnings: &mut Vec<String>,
+) -> OtioTrack {
+    let mut clips = track.clips().iter().collect::<Vec<_>>();
+    clips.sort_by_key(|clip| clip.range().start().unwrap_or_default());
+
+    let mut children = Vec::new();
+    let mut time = TimeCode::default();
+    let mut previous: Option<ClipId> = None;
+
+    for clip in clips {
+        let range = clip.range();
+        let start = range.start().unwrap_or_default();
+
+        let Some(end) = range.end() else {
+            warnings.push(format!("Skipped clip \"{}\", it has no end", clip.name()));
+            continue;
+        };
+
+        if start < time {
+            warnings.push(format!(
+                "Skipped clip \"{}\", it overlaps another clip on track \"{}\"",
+                clip.name(),
+                track.name()
+            ));
+            continue;
+        }
+
+        if start > time {
+            children.push(Item::Gap {
+                source_range: TimeRange::new(TimeCode::default(), start - time, rate),
+            });
+            previous = None;
+        }
+
+        if let Some(transition) = track
+            .transitions()
+            .iter()
+            .find(|transition| transition.to == clip.id() && Some(transition.from) == previous)
+        {
+            children.push(Item::Transition(export_transition(
+                transition, rate, warnings,
+            )));
+        }
+
+        children.push(Item::Clip(export_clip(
+            clip,
+            end - start,
+            rate,
+            media_url,
+            warnings,
+        )));
+        time = end;
+        previous = Some(clip.id());
+    }
+
+    OtioTrack {
+        name: track.name().to_string(),
+        kind: match track.kind() {
+            TrackKind::Video => "Video",
+            TrackKind::Audio => "Audio",
+        }
+        .to_string(),
+        children,
+        markers: Vec::new(),
+        enabled: track.enabled(),
+    }
+}
+
+fn export_clip(
+    clip: &Clip,
+    duration: TimeCode,
+    rate: f64,
+    media_url: &mut impl FnMut(&Clip) -> Option<String>,
+    warnings: &mut Vec<String>,
+) -> OtioClip {
+    let time_remap = clip.time_remap();
+    let mut effects = Vec::new();
+
+    let source_start = if let Some(freeze) = time_remap.freeze {
+        effects.push(serde_json::json!({
+            "OTIO_SCHEMA": "FreezeFrame.1",
+            "effect_name": "FreezeFrame",
+            "time_scalar": 0.0,
+        }));
+        freeze
+    } else if time_remap.speed != 1.0 {
+        effects.push(serde_json::json!({
+            "OTIO_SCHEMA": "LinearTimeWarp.1",
+            "effect_name": "LinearTimeWarp",
+            "time_scalar": time_remap.speed,
+        }));
+        TimeCode::new((clip.content_offset().value() as f64 * time_remap.speed) as i64)
+    } else {
+        clip.content_offset()
+    };
+
+    if time_remap.reverse || time_remap.curve.is_some() || time_remap.looping != Default::default()
+    {
+        warnings.push(format!(
+            "Time remapping of clip \"{}\" is only exported as a constant speed",
+            clip.name()
+        ));
+    }
+
+    if !clip.children().is_empty() {
+        warnings.push(format!(
+            "Children of clip \"{}\" are not exported",
+            clip.name()
+        ));
+    }
+
+    if !clip.effects().is_empty() {
+        warnings.push(format!(
+            "Effects of clip \"{}\" are not exported",
+            clip.name()
+        ));
+    }
+
+    let media_reference = match media_url(clip) {
+        Some(target_url) => OtioMediaReference::External {
+            target_url,
+            available_range: clip
+                .video()
+                .and_then(|video| video.duration())
+                .map(|duration| TimeRange::new(TimeCode::default(), duration, rate)),
+        },
+        None => OtioMediaReference::Missing {},
+    };
+
+    OtioClip {
+        name: clip.name().to_string(),
+        source_range: Some(TimeRange::new(source_start, duration, rate)),
+        media_reference: Some(media_reference),
+        media_references: HashMap::new(),
+        active_media_reference_key: None,
+        effects,
+        markers: Vec::new(),
+        enabled: clip.enabled(),
+    }
+}
+
+fn export_transition(
+    transition: &Transition,
+    rate: f64,
+    warnings: &mut Vec<String>,
+) -> OtioTransition {
+    let transition_type = match transition.kind {
+        TransitionKind::Crossfade => "SMPTE_Dissolve",
+        _ => {
+            warnings.push(format!(
+                "Transition {:?} is exported as a custom transition",
+                transition.kind
+            ));
+            "Custom_Transition"
+        }
+    };
+
+    let half = TimeCode::new(transition.duration.value() / 2);
+
+    OtioTransition {
+        name: String::new(),
+        transition_type: transition_type.to_string(),
+        in_offset: RationalTime::new(half, rate),
+        out_offset: RationalTime::new(transition.duration - half, rate),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn named(name: &str, range: std::ops::Range<f64>) -> Clip {
+        let mut clip = Clip::new(range);
+        clip.set_name(name);
+        clip
+    }
+
+    fn find<'a>(track: &'a Track, name: &str) -> &'a Clip {
+        track
+            .clips()
+            .iter()
+            .find(|clip| clip.name() == name)
+            .unwrap_or_else(|| panic!("clip {name} is missing"))
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut first = named("first", 0.0..2.0);
+        first.set_content_offset(TimeCode::from_seconds(1.0));
+
+        // After a 1 second gap
+        let mut second = named("second", 3.0..5.0);
+        second.set_content_offset(TimeCode::from_seconds(0.5));
+        second.set_time_remap(TimeRemap::with_speed(2.0));
+
+        let third = named("third", 5.0..7.0);
+
+        let mut track = Track::video("V1");
+        track.add_transition(Transition::crossfade(
+            second.id(),
+            third.id(),
+            TimeCode::from_seconds(1.0),
+        ));
+        track.add_clip(first);
+        track.add_clip(second);
+        track.add_clip(third);
+
+        let mut project = Project::new();
+        project.add_track(track);
+
+        let exported = export(&project, "Round trip", 24.0, |clip| {
+            Some(format!("{}.mov", clip.name()))
+        });
+        assert!(exported.warnings.is_empty(), "{:?}", exported.warnings);
+
+        let mut urls = Vec::new();
+        let imported = import(&exported.otio, |media| {
+            urls.push(media.target_url.clone());
+            None
+        })
+        .unwrap();
+
+        assert_eq!(
+            urls,
+            ["first.mov", "second.mov", "third.mov"].map(|url| Some(url.to_string()))
+        );
+
+        let track = &imported.project.tracks()[0];
+        assert_eq!(track.name(), "V1");
+        assert_eq!(track.clips().len(), 3);
+
+        let first = find(track, "first");
+        assert_eq!(first.range().start(), Some(TimeCode::from_seconds(0.0)));
+        assert_eq!(first.range().end(), Some(TimeCode::from_seconds(2.0)));
+        assert_eq!(first.content_offset(), TimeCode::from_seconds(1.0));
+
+        let second = find(track, "second");
+        assert_eq!(second.range().start(), Some(TimeCode::from_seconds(3.0)));
+        assert_eq!(second.range().end(), Some(TimeCode::from_seconds(5.0)));
+        assert_eq!(second.content_offset(), TimeCode::from_seconds(0.5));
+        assert_eq!(second.time_remap().speed, 2.0);
+
+        let third = find(track, "third");
+        assert_eq!(third.range().start(), Some(TimeCode::from_seconds(5.0)));
+        assert_eq!(third.range().end(), Some(TimeCode::from_seconds(7.0)));
+
+        assert_eq!(
+            track.transitions(),
+            [Transition::crossfade(
+                second.id(),
+                third.id(),
+                TimeCode::from_seconds(1.0)
+            )]
+        );
+    }
+
+    fn time_range(start: f64, duration: f64) -> Value {
+        serde_json::json!({
+            "OTIO_SCHEMA": "TimeRange.1",
+            "start_time": { "OTIO_SCHEMA": "RationalTime.1", "rate": 24.0, "value": start },
+            "duration": { "OTIO_SCHEMA": "RationalTime.1", "rate": 24.0, "value": duration },
+        })
+    }
+
+    fn external(target_url: &str) -> Value {
+        // Media that starts at 01:00:00:00
+        serde_json::json!({
+            "OTIO_SCHEMA": "ExternalReference.1",
+            "target_url": target_url,
+            "available_range": time_range(86400.0, 240.0),
+        })
+    }
+
+    #[test]
+    fn import_clip_2_media_references() {
+        let otio = serde_json::json!({
+            "OTIO_SCHEMA": "Timeline.1",
+            "name": "Clip.2",
+            "tracks": {
+                "OTIO_SCHEMA": "Stack.1",
+                "children": [{
+                    "OTIO_SCHEMA": "Track.1",
+                    "name": "V1",
+                    "kind": "Video",
+                    "children": [
+                        {
+                            "OTIO_SCHEMA": "Clip.2",
+                            "name": "proxy",
+                            "source_range": time_range(86424.0, 48.0),
+                            "media_references": {
+                                "DEFAULT_MEDIA": external("full.mov"),
+                                "proxy": external("proxy.mov"),
+                            },
+                            "active_media_reference_key": "proxy",
+                        },
+                        {
+                            "OTIO_SCHEMA": "Clip.2",
+                            "name": "default",
+                            "source_range": time_range(86400.0, 24.0),
+                            "media_references": {
+                                "DEFAULT_MEDIA": external("full.mov"),
+                            },
+                        },
+                    ],
+                }],
+            },
+        });
+
+        let mut media = Vec::new();
+        let imported = import(&otio.to_string(), |reference| {
+            media.push((reference.target_url.clone(), reference.available_range));
+            None
+        })
+        .unwrap();
+
+        assert_eq!(media.len(), 2);
+        assert_eq!(media[0].0.as_deref(), Some("proxy.mov"));
+        assert_eq!(media[1].0.as_deref(), Some("full.mov"));
+
+        let available_range = media[0].1.unwrap();
+        assert_eq!(
+            available_range.start(),
+            Some(TimeCode::from_seconds(3600.0))
+        );
+        assert_eq!(available_range.end(), Some(TimeCode::from_seconds(3610.0)));
+
+        let track = &imported.project.tracks()[0];
+
+        // The source range is relative to the start of the media
+        let proxy = find(track, "proxy");
+        assert_eq!(proxy.range().start(), Some(TimeCode::from_seconds(0.0)));
+        assert_eq!(proxy.range().end(), Some(TimeCode::from_seconds(2.0)));
+        assert_eq!(proxy.content_offset(), TimeCode::from_seconds(1.0));
+
+        let default = find(track, "default");
+        assert_eq!(default.range().start(), Some(TimeCode::from_seconds(2.0)));
+        assert_eq!(default.range().end(), Some(TimeCode::from_seconds(3.0)));
+        assert_eq!(default.content_offset(), TimeCode::from_seconds(0.0));
+    }
+}
@@ -0,0 +1,202 @@
+//! SMPTE timecode (`HH:MM:SS:FF`, or `HH:MM:SS;FF` for drop frame)
+
+use vide_common::{prelude::TimeCode, time_code::UnboundedTimecodeRange, types::FramesPerSecond};
+
+use crate::time_code;
+
+/// The frame rate timecode is counted in. NTSC rates like 29.97 are counted at the
+/// next whole rate, drop frame timecode skips frame numbers to stay in sync with the
+/// clock.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimecodeRate {
+    pub frames_per_second: FramesPerSecond,
+    pub drop_frame: bool,
+}
+
+impl TimecodeRate {
+    pub fn new(frames_per_second: FramesPerSecond, drop_frame: bool) -> Self {
+        Self {
+            frames_per_second,
+            drop_frame,
+        }
+    }
+
+    /// Frames per second used to count frames
+    pub fn nominal(&self) -> i64 {
+        self.frames_per_second.round() as i64
+    }
+
+    /// Frames per second of the video, NTSC rates are `nominal * 1000 / 1001`
+    pub fn actual(&self) -> f64 {
+        let nominal = self.nominal() as f64;
+
+        if (self.frames_per_second - nominal).abs() > 0.001 {
+            nominal * 1000.0 / 1001.0
+        } else {
+            nominal
+        }
+    }
+
+    /// Frames that are skipped every minute (except every tenth) in drop frame timecode
+    fn dropped_frames(&self) -> i64 {
+        if self.drop_frame {
+            (self.frames_per_second * 0.066666).round() as i64
+        } else {
+            0
+        }
+    }
+
+    /// The frame number of a timecode like `01:00:00:00`
+    pub fn parse_frames(&self, text: &str) -> Option<i64> {
+        let parts = text
+            .split([':', ';', '.', ','])
+            .map(|part| part.parse::<i64>().ok())
+            .collect::<Option<Vec<_>>>()?;
+
+        let [hours, minutes, seconds, frames] = parts[..] else {
+            return None;
+        };
+
+        let nominal = self.nominal();
+        if minutes >= 60 || seconds >= 60 || frames >= nominal {
+            return None;
+        }
+
+        // Drop frame timecode skips the first frame numbers of every minute, except
+        // every tenth
+        if seconds == 0 && minutes % 10 != 0 && frames < self.dropped_frames() {
+            return None;
+        }
+
+        let total_minutes = hours * 60 + minutes;
+
+        Some(
+            (total_minutes * 60 + seconds) * nominal + frames
+                - self.dropped_frames() * (total_minutes - total_minutes / 10),
+        )
+    }
+
+    /// The time of a timecode like `01:00:00:00`
+    pub fn parse(&self, text: &str) -> Option<TimeCode> {
+        Some(self.time_code(self.parse_frames(text)?))
+    }
+
+    /// The time `frames` frames in
+    pub fn time_code(&self, frames: i64) -> TimeCode {
+        time_code(frames as f64, self.actual())
+    }
+
+    /// The range between two timecodes, `out` is exclusive
+    pub fn range(&self, r#in: &str, out: &str) -> Option<UnboundedTimecodeRange> {
+        Some(UnboundedTimecodeRange::new(
+            Some(self.parse(r#in)?),
+            Some(self.parse(out)?),
+        ))
+    }
+
+    /// Format a frame number as timecode
+    pub fn format_frames(&self, mut frames: i64) -> String {
+        let nominal = self.nominal();
+        let dropped = self.dropped_frames();
+
+        if dropped > 0 {
+            let frames_per_minute = nominal * 60 - dropped;
+            let frames_per_ten_minutes = frames_per_minute * 10 + dropped;
+
+            let tens = frames / frames_per_ten_minutes;
+            let remainder = frames % frames_per_ten_minutes;
+
+            frames += dropped * 9 * tens;
+            if remainder > dropped {
+                frames += dropped * ((remainder - dropped) / frames_per_minute);
+            }
+        }
+
+        format!(
+            "{:02}:{:02}:{:02}{}{:02}",
+            frames / (nominal * 3600),
+            frames / (nominal * 60) % 60,
+            frames / nominal % 60,
+            if self.drop_frame { ';' } else { ':' },
+            frames % nominal
+        )
+    }
+
+    /// Format a time as timecode, rounded down to a whole frame
+    pub fn format(&self, time_code: TimeCode) -> String {
+        self.format_frames((time_code.seconds() * self.actual() + 1e-6).floor() as i64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DROP_FRAME: TimecodeRate = TimecodeRate {
+        frames_per_second: 29.97,
+        drop_frame: true,
+    };
+
+    #[test]
+    fn drop_frame_ten_minute_boundary() {
+        let last = DROP_FRAME.parse_frames("00:09:59;29").unwrap();
+        let next = DROP_FRAME.parse_frames("00:10:00;00").unwrap();
+
+        assert_eq!(last, 17981);
+        assert_eq!(next, last + 1);
+        assert_eq!(DROP_FRAME.format_frames(last), "00:09:59;29");
+        assert_eq!(DROP_FRAME.format_frames(next), "00:10:00;00");
+    }
+
+    #[test]
+    fn drop_frame_minute_boundary() {
+        let last = DROP_FRAME.parse_frames("00:00:59;29").unwrap();
+
+        assert_eq!(DROP_FRAME.parse_frames("00:01:00;02"), Some(last + 1));
+        assert_eq!(DROP_FRAME.format_frames(last + 1), "00:01:00;02");
+    }
+
+    #[test]
+    fn drop_frame_rejects_dropped_labels() {
+        assert_eq!(DROP_FRAME.parse_frames("00:01:00;00"), None);
+        assert_eq!(DROP_FRAME.parse_frames("00:01:00;01"), None);
+        assert_eq!(DROP_FRAME.parse_frames("01:59:00;01"), None);
+
+        // Every tenth minute keeps all frame numbers
+        assert!(DROP_FRAME.parse_frames("00:20:00;00").is_some());
+        assert!(DROP_FRAME.parse_frames("01:00:00;01").is_some());
+
+        // Non drop frame timecode has no dropped labels
+        let non_drop = TimecodeRate::new(29.97, false);
+        assert_eq!(non_drop.parse_frames("00:01:00:00"), Some(1800));
+    }
+
+    #[test]
+    fn drop_frame_round_trip() {
+        for rate in [DROP_FRAME, TimecodeRate::new(59.94, true)] {
+            for frames in 0..40_000 {
+                let text = rate.format_frames(frames);
+                assert_eq!(rate.parse_frames(&text), Some(frames), "{text}");
+            }
+        }
+    }
+
+    #[test]
+    fn drop_frame_stays_in_sync_with_the_clock() {
+        let time = DROP_FRAME.parse("01:00:00;00").unwrap();
+
+        // An hour of drop frame timecode is 3.6 frames short of an hour
+        assert!((time.seconds() - 3600.0).abs() < 0.01, "{}", time.seconds());
+        assert_eq!(DROP_FRAME.format(time), "01:00:00;00");
+    }
+
+    #[test]
+    fn non_drop_frame() {
+        let rate = TimecodeRate::new(25.0, false);
+
+        assert_eq!(rate.parse_frames("01:00:00:10"), Some(90_010));
+        assert_eq!(rate.format_frames(90_010), "01:00:00:10");
+        assert_eq!(rate.parse_frames("00:00:00:25"), None);
+        assert_eq!(rate.parse_frames("00:00:00"), None);
+    }
+}
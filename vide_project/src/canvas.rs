@@ -0,0 +1,26 @@
+use serde::{Deserialize, Serialize};
+use vide_common::{prelude::Color, types::Resolution};
+
+/// The area clips are laid out in, independent of the output resolution. The render
+/// configuration decides how it is scaled to the output, see
+/// [`Scaling`](vide_common::config::Scaling).
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Canvas {
+    pub size: Resolution,
+    /// Fills the canvas behind all clips
+    pub background: Color,
+}
+
+impl Canvas {
+    pub fn new(size: Resolution) -> Self {
+        Self {
+            size,
+            background: Color::TRANSPARENT,
+        }
+    }
+
+    pub fn with_background(mut self, background: impl Into<Color>) -> Self {
+        self.background = background.into();
+        self
+    }
+}
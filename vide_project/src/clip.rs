@@ -1,22 +1,150 @@
+use std::{
+    fmt::Display,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use euler::{vec2, vec3, Mat4, Quat, Trs, Vec2};
+use serde::{Deserialize, Deserializer, Serialize};
+use vide_animate::AnimatedProperty;
 use vide_common::{
-    time_code::UnboundedTimecodeRange, transform::Transform, visible_object::VisibleObject,
+    blend_mode::BlendMode, effect::Effect, layout::Position, prelude::TimeCode,
+    time_code::UnboundedTimecodeRange, visible_object::VisibleObject, FrameInfo,
+};
+
+use crate::{
+    matte::{Mask, MatteMode, TrackMatte},
+    time_remap::{Looping, TimeRemap},
 };
 
-#[derive(Debug)]
+/// Identifies a clip across edits, cloning a clip keeps its id
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+pub struct ClipId(u64);
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+impl ClipId {
+    fn next() -> Self {
+        Self(NEXT_ID.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+impl Display for ClipId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "#{}", self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for ClipId {
+    /// Loaded ids are kept, new ids are never handed out again
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let id = u64::deserialize(deserializer)?;
+        NEXT_ID.fetch_max(id + 1, Ordering::Relaxed);
+        Ok(Self(id))
+    }
+}
+
+/// How a clip without an end gets one, see [`Project::infer_durations`](crate::Project::infer_durations)
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DurationInference {
+    /// End when the video does, for example after the last keyframe of a shape or
+    /// the end of a nested project
+    #[default]
+    Content,
+    /// Stay unbounded, the clip lasts as long as its parent
+    Off,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Clip {
+    id: ClipId,
+    #[serde(default)]
+    name: String,
     range: UnboundedTimecodeRange,
     children: Vec<Clip>,
     video_source: Option<Box<dyn VisibleObject>>,
-    transform: Transform,
+    position: AnimatedProperty<Position>,
+    rotation: AnimatedProperty<f64>,
+    #[serde(with = "vide_animate::serialize::vec2")]
+    scale: AnimatedProperty<Vec2>,
+    anchor: AnimatedProperty<Position>,
+    opacity: AnimatedProperty<f64>,
+    blend_mode: BlendMode,
+    track_matte: Option<TrackMatte>,
+    masks: Vec<Mask>,
+    effects: Vec<Box<dyn Effect>>,
+    time_remap: TimeRemap,
+    content_offset: TimeCode,
+    #[serde(default)]
+    animation_offset: TimeCode,
+    adjustment_layer: bool,
+    null: bool,
+    transform_parent: Option<ClipId>,
+    enabled: bool,
+    #[serde(default)]
+    duration_inference: DurationInference,
 }
 
 impl Clip {
     pub fn new(range: impl Into<UnboundedTimecodeRange>) -> Self {
         Self {
+            id: ClipId::next(),
+            name: String::new(),
             range: range.into(),
             children: Vec::new(),
             video_source: None,
-            transform: Transform::new(),
+            position: AnimatedProperty::default(),
+            rotation: AnimatedProperty::default(),
+            scale: AnimatedProperty::with_default(vec2!(1.0)),
+            anchor: AnimatedProperty::default(),
+            opacity: AnimatedProperty::with_default(1.0),
+            blend_mode: BlendMode::Normal,
+            track_matte: None,
+            masks: Vec::new(),
+            effects: Vec::new(),
+            time_remap: TimeRemap::new(),
+            content_offset: TimeCode::default(),
+            animation_offset: TimeCode::default(),
+            adjustment_layer: false,
+            null: false,
+            transform_parent: None,
+            enabled: true,
+            duration_inference: DurationInference::Content,
+        }
+    }
+
+    /// A clip that is never rendered, but that other clips can use as their
+    /// transform parent
+    pub fn null(range: impl Into<UnboundedTimecodeRange>) -> Self {
+        Self {
+            null: true,
+            ..Self::new(range)
+        }
+    }
+
+    pub fn id(&self) -> ClipId {
+        self.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn set_name(&mut self, name: impl Into<String>) {
+        self.name = name.into();
+    }
+
+    /// Clone this clip, giving it and all of its children new ids
+    pub fn duplicate(&self) -> Self {
+        let mut clip = self.clone();
+        clip.reassign_ids();
+        clip
+    }
+
+    fn reassign_ids(&mut self) {
+        self.id = ClipId::next();
+        self.children.iter_mut().for_each(Clip::reassign_ids);
+        if let Some(track_matte) = &mut self.track_matte {
+            track_matte.source.reassign_ids();
         }
     }
 
@@ -24,12 +152,80 @@ impl Clip {
         self.video_source = Some(Box::new(source));
     }
 
+    /// Set the duration of this clip to [`Clip::natural_duration`], if it has one
     pub fn infer_duration(&mut self) {
-        if let Some(duration) = self.video_source.as_ref().and_then(|v| v.duration()) {
+        if let Some(duration) = self.natural_duration() {
             self.range.set_duration(duration);
         }
     }
 
+    /// Infer the duration of this clip (if it has no end) and all of its children,
+    /// following their [`DurationInference`]
+    pub(crate) fn infer_durations(&mut self) {
+        self.children.iter_mut().for_each(Clip::infer_durations);
+        if let Some(track_matte) = &mut self.track_matte {
+            track_matte.source.infer_durations();
+        }
+
+        self.range.set_end(self.inferred_own_end());
+    }
+
+    /// The end of [`Clip::range`] after [`Clip::infer_durations`], without modifying
+    /// the clip
+    pub fn inferred_end(&self) -> Option<TimeCode> {
+        self.inferred_own_end()
+            .or_else(|| self.children.iter().filter_map(Clip::inferred_end).max())
+    }
+
+    /// The end [`Clip::infer_durations`] gives this clip itself, ignoring the ends its
+    /// children give it
+    fn inferred_own_end(&self) -> Option<TimeCode> {
+        if self.range.end().is_some() || self.duration_inference == DurationInference::Off {
+            return self.range.end();
+        }
+
+        // Children that play longer than the video keep the clip open
+        let children = self.children.iter().filter_map(Clip::inferred_end).max();
+        let mut range = self.range;
+        range.set_duration(
+            self.natural_duration()
+                .filter(|duration| children.is_none_or(|end| *duration > end))?,
+        );
+        range.end()
+    }
+
+    /// How long it takes the video of this clip to play to its end, with time
+    /// remapping applied. `None` if the video has no end, or never reaches it
+    /// because it is frozen, loops forever or follows a curve.
+    pub fn natural_duration(&self) -> Option<TimeCode> {
+        let duration = self.video_source.as_ref()?.duration()?;
+        let remap = &self.time_remap;
+
+        if remap.freeze.is_some() || remap.curve.is_some() || remap.speed <= 0.0 {
+            return None;
+        }
+
+        let times = match remap.looping {
+            Looping::Off => 1,
+            Looping::Times(times) => times.max(1),
+            Looping::Forever => return None,
+        };
+
+        let local = (duration.value() * times as i64) as f64 / remap.speed;
+        Some(TimeCode::new(
+            (local.round() as i64 - self.content_offset.value()).max(0),
+        ))
+    }
+
+    pub fn duration_inference(&self) -> DurationInference {
+        self.duration_inference
+    }
+
+    /// Whether this clip adopts the duration of its video when it has no end
+    pub fn set_duration_inference(&mut self, duration_inference: DurationInference) {
+        self.duration_inference = duration_inference;
+    }
+
     pub fn range(&self) -> UnboundedTimecodeRange {
         UnboundedTimecodeRange::new(
             self.range.start(),
@@ -39,19 +235,380 @@ impl Clip {
         )
     }
 
+    pub fn set_range(&mut self, range: impl Into<UnboundedTimecodeRange>) {
+        self.range = range.into();
+    }
+
+    pub(crate) fn range_mut(&mut self) -> &mut UnboundedTimecodeRange {
+        &mut self.range
+    }
+
     pub fn add_clip(&mut self, clip: Clip) {
         self.children.push(clip);
     }
 
-    pub fn transform(&self) -> Transform {
-        self.transform
+    /// Find a clip by id in this clip's children (recursively)
+    pub fn find_clip(&self, id: ClipId) -> Option<&Clip> {
+        self.children.iter().find_map(|clip| {
+            if clip.id == id {
+                Some(clip)
+            } else {
+                clip.find_clip(id)
+            }
+        })
+    }
+
+    pub fn find_clip_mut(&mut self, id: ClipId) -> Option<&mut Clip> {
+        self.children.iter_mut().find_map(|clip| {
+            if clip.id == id {
+                Some(clip)
+            } else {
+                clip.find_clip_mut(id)
+            }
+        })
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Disabled clips (and their children) are skipped by the renderer
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    pub fn position(&self) -> &AnimatedProperty<Position> {
+        &self.position
+    }
+
+    pub fn set_position(&mut self, position: AnimatedProperty<Position>) {
+        self.position = position;
+    }
+
+    /// Rotation in degrees, clockwise
+    pub fn rotation(&self) -> &AnimatedProperty<f64> {
+        &self.rotation
+    }
+
+    pub fn set_rotation(&mut self, rotation: AnimatedProperty<f64>) {
+        self.rotation = rotation;
+    }
+
+    pub fn scale(&self) -> &AnimatedProperty<Vec2> {
+        &self.scale
+    }
+
+    pub fn set_scale(&mut self, scale: AnimatedProperty<Vec2>) {
+        self.scale = scale;
+    }
+
+    /// The point (in the parent's space) that is moved to `position`, and that
+    /// rotation and scale are applied around
+    pub fn anchor(&self) -> &AnimatedProperty<Position> {
+        &self.anchor
+    }
+
+    pub fn set_anchor(&mut self, anchor: AnimatedProperty<Position>) {
+        self.anchor = anchor;
+    }
+
+    pub fn opacity(&self) -> &AnimatedProperty<f64> {
+        &self.opacity
+    }
+
+    pub fn set_opacity(&mut self, opacity: AnimatedProperty<f64>) {
+        self.opacity = opacity;
+    }
+
+    pub fn blend_mode(&self) -> BlendMode {
+        self.blend_mode
+    }
+
+    /// Set how this clip is blended onto the clips below it
+    pub fn set_blend_mode(&mut self, blend_mode: BlendMode) {
+        self.blend_mode = blend_mode;
+    }
+
+    pub fn track_matte(&self) -> Option<&TrackMatte> {
+        self.track_matte.as_ref()
+    }
+
+    pub fn track_matte_mut(&mut self) -> Option<&mut TrackMatte> {
+        self.track_matte.as_mut()
+    }
+
+    /// Only show this clip where `matte` is visible (or invisible, depending on `mode`)
+    pub fn set_track_matte(&mut self, mode: MatteMode, matte: Clip) {
+        self.track_matte = Some(TrackMatte {
+            mode,
+            source: Box::new(matte),
+        });
+    }
+
+    pub fn remove_track_matte(&mut self) -> Option<TrackMatte> {
+        self.track_matte.take()
+    }
+
+    pub fn masks(&self) -> &[Mask] {
+        &self.masks
+    }
+
+    pub fn masks_mut(&mut self) -> &mut Vec<Mask> {
+        &mut self.masks
+    }
+
+    pub fn add_mask(&mut self, mask: Mask) {
+        self.masks.push(mask);
+    }
+
+    pub fn effects(&self) -> &[Box<dyn Effect>] {
+        &self.effects
+    }
+
+    pub fn effects_mut(&mut self) -> &mut Vec<Box<dyn Effect>> {
+        &mut self.effects
+    }
+
+    /// Add an effect to the end of this clip's effect stack, effects are applied in
+    /// order after masks and before the track matte
+    pub fn add_effect(&mut self, effect: impl Effect + 'static) {
+        self.effects.push(Box::new(effect));
+    }
+
+    pub fn is_adjustment_layer(&self) -> bool {
+        self.adjustment_layer
+    }
+
+    /// Adjustment layers apply their effects to everything below them in the same
+    /// parent, instead of rendering children or video of their own. Opacity, masks and
+    /// the track matte control how much of the result replaces what is below.
+    pub fn set_adjustment_layer(&mut self, adjustment_layer: bool) {
+        self.adjustment_layer = adjustment_layer;
+    }
+
+    pub fn is_null(&self) -> bool {
+        self.null
+    }
+
+    pub fn set_null(&mut self, null: bool) {
+        self.null = null;
+    }
+
+    pub fn transform_parent(&self) -> Option<ClipId> {
+        self.transform_parent
+    }
+
+    /// Inherit the transform of another clip anywhere in the project instead of the
+    /// transform of the parent in the clip tree. Where this clip is composited doesn't
+    /// change. Parenting that ends up in a cycle is ignored.
+    pub fn set_transform_parent(&mut self, transform_parent: Option<ClipId>) {
+        self.transform_parent = transform_parent;
+    }
+
+    pub fn time_remap(&self) -> &TimeRemap {
+        &self.time_remap
+    }
+
+    pub fn time_remap_mut(&mut self) -> &mut TimeRemap {
+        &mut self.time_remap
+    }
+
+    /// Change how fast, in which direction or how often the children and video of
+    /// this clip play
+    pub fn set_time_remap(&mut self, time_remap: TimeRemap) {
+        self.time_remap = time_remap;
+    }
+
+    /// How long the children and video of this clip take to play once, without
+    /// time remapping
+    pub fn content_duration(&self) -> Option<TimeCode> {
+        self.children
+            .iter()
+            .filter_map(|c| c.range().end())
+            .chain(self.video_source.as_ref().and_then(|v| v.duration()))
+            .max()
+    }
+
+    /// How far into its content this clip starts playing
+    pub fn content_offset(&self) -> TimeCode {
+        self.content_offset
+    }
+
+    pub fn set_content_offset(&mut self, content_offset: TimeCode) {
+        self.content_offset = content_offset;
+    }
+
+    /// The range the children of this clip are placed in, given the absolute range of
+    /// the clip. Children see remapped time, which can run past the end of the clip
+    /// (at a higher speed, for example), so they are only limited by the content.
+    pub fn content_range(&self, absolute_range: UnboundedTimecodeRange) -> UnboundedTimecodeRange {
+        if self.time_remap.is_identity() && self.content_offset.value() == 0 {
+            return absolute_range;
+        }
+
+        let start = absolute_range.start().unwrap_or_default();
+
+        UnboundedTimecodeRange::new(
+            Some(start),
+            self.content_duration().map(|duration| start + duration),
+        )
+    }
+
+    /// Map a local time of this clip to the time its children and video see
+    pub fn content_time(&self, local: TimeCode) -> TimeCode {
+        let local = local + self.content_offset;
+
+        if self.time_remap.is_identity() {
+            return local;
+        }
+
+        self.time_remap.remap(local, self.content_duration())
+    }
+
+    pub fn animation_offset(&self) -> TimeCode {
+        self.animation_offset
+    }
+
+    /// Where in its own animation (transform, opacity, masks and effects) this clip
+    /// starts, set when a clip is split or trimmed so the animation doesn't restart
+    pub fn set_animation_offset(&mut self, animation_offset: TimeCode) {
+        self.animation_offset = animation_offset;
+    }
+
+    /// The frame info the animated properties of this clip are evaluated at, given its
+    /// local frame info
+    pub fn animation_frame_info(&self, local_frame_info: &FrameInfo) -> FrameInfo {
+        FrameInfo {
+            time_code: local_frame_info.time_code + self.animation_offset,
+            ..*local_frame_info
+        }
+    }
+
+    /// Evaluate the transform of this clip at a local time, relative to its parent
+    pub fn transform_at(&self, local_frame_info: &FrameInfo) -> Mat4 {
+        let time_code = local_frame_info.time_code + self.animation_offset;
+        let resolution = local_frame_info.resolution;
+
+        let position = self.position.evaluate(time_code).resolve(resolution);
+        let rotation = self.rotation.evaluate(time_code);
+        let scale = self.scale.evaluate(time_code);
+        let anchor = self.anchor.evaluate(time_code).resolve(resolution);
+
+        let transform = Trs::new(
+            vec3!(position, 0.0),
+            Quat::axis_angle(vec3!(0.0, 0.0, -1.0), rotation.to_radians() as f32),
+            vec3!(scale, 1.0),
+        )
+        .matrix();
+
+        let anchor_offset = Trs::new(
+            vec3!(-anchor.x, -anchor.y, 0.0),
+            Quat::identity(),
+            vec3!(1.0),
+        )
+        .matrix();
+
+        transform * anchor_offset
+    }
+
+    /// Evaluate the opacity of this clip at a local time
+    pub fn opacity_at(&self, time_code: TimeCode) -> f64 {
+        self.opacity
+            .evaluate(time_code + self.animation_offset)
+            .clamp(0.0, 1.0)
+    }
+
+    pub fn video(&self) -> Option<&dyn VisibleObject> {
+        self.video_source.as_deref()
+    }
+
+    pub fn set_video(&mut self, source: Option<Box<dyn VisibleObject>>) {
+        self.video_source = source;
     }
 
     pub fn video_mut(&mut self) -> Option<&mut Box<dyn VisibleObject>> {
         self.video_source.as_mut()
     }
 
+    pub fn children(&self) -> &[Clip] {
+        &self.children
+    }
+
     pub fn children_mut(&mut self) -> &mut [Clip] {
         &mut self.children
     }
+
+    pub(crate) fn children_vec_mut(&mut self) -> &mut Vec<Clip> {
+        &mut self.children
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use vide_common::types::TimeUnit;
+
+    use super::*;
+
+    #[test]
+    fn content_time() {
+        let mut clip = Clip::new(0.0..2.0);
+        assert_eq!(
+            clip.content_time(TimeCode::from_seconds(1.0)),
+            TimeCode::from_seconds(1.0)
+        );
+
+        clip.set_content_offset(TimeCode::from_seconds(1.0));
+        assert_eq!(
+            clip.content_time(TimeCode::from_seconds(0.5)),
+            TimeCode::from_seconds(1.5)
+        );
+
+        clip.set_time_remap(TimeRemap::with_speed(2.0));
+        assert_eq!(
+            clip.content_time(TimeCode::from_seconds(0.5)),
+            TimeCode::from_seconds(3.0)
+        );
+    }
+
+    #[test]
+    fn content_time_loops_over_children() {
+        let mut clip = Clip::new(0.0..4.0);
+        clip.add_clip(Clip::new(0.0..1.5));
+        clip.set_time_remap(TimeRemap {
+            looping: Looping::Forever,
+            ..TimeRemap::new()
+        });
+
+        assert_eq!(clip.content_duration(), Some(TimeCode::from_seconds(1.5)));
+        assert_eq!(
+            clip.content_time(TimeCode::from_seconds(2.0)),
+            TimeCode::from_seconds(0.5)
+        );
+    }
+
+    #[test]
+    fn content_range() {
+        let range = UnboundedTimecodeRange::new(
+            Some(TimeCode::from_seconds(1.0)),
+            Some(TimeCode::from_seconds(3.0)),
+        );
+
+        let mut clip = Clip::new(0.0..2.0);
+        clip.add_clip(Clip::new(0.0..4.0));
+
+        let identity = clip.content_range(range);
+        assert_eq!(identity.start(), Some(TimeCode::from_seconds(1.0)));
+        assert_eq!(identity.end(), Some(TimeCode::from_seconds(3.0)));
+
+        // At double speed the clip plays all 4 seconds of its child
+        clip.set_time_remap(TimeRemap::with_speed(2.0));
+        let remapped = clip.content_range(range);
+        assert_eq!(remapped.start(), Some(TimeCode::from_seconds(1.0)));
+        assert_eq!(remapped.end(), Some(TimeCode::from_seconds(5.0)));
+
+        let child = clip.children()[0].range().make_absolute(remapped);
+        assert!(child.contains(
+            TimeCode::from_seconds(1.0) + clip.content_time(TimeCode::from_seconds(1.5))
+        ));
+    }
 }
@@ -0,0 +1,568 @@
+//! Non-destructive timeline editing. All times are in the space of the edited clip's
+//! parent, the same space as [`Clip::range`]. Clips on locked tracks are left alone.
+
+use vide_common::prelude::TimeCode;
+
+use crate::{
+    clip::{Clip, ClipId},
+    track::ClipParent,
+    Project,
+};
+
+/// Find the list of clips that contains the clip with `id`, and its index in it
+fn find_siblings_mut(clips: &mut Vec<Clip>, id: ClipId) -> Option<(&mut Vec<Clip>, usize)> {
+    if let Some(index) = clips.iter().position(|clip| clip.id() == id) {
+        return Some((clips, index));
+    }
+
+    for clip in clips.iter_mut() {
+        if let Some(found) = find_siblings_mut(clip.children_vec_mut(), id) {
+            return Some(found);
+        }
+    }
+
+    None
+}
+
+/// Shift every clip in `clips` that starts at or after `from` by `offset`
+fn ripple(clips: &mut [Clip], from: TimeCode, offset: TimeCode) {
+    for clip in clips {
+        if clip.range().start().unwrap_or_default() >= from {
+            clip.range_mut().shift(offset);
+        }
+    }
+}
+
+impl Project {
+    pub fn find_clip(&self, id: ClipId) -> Option<&Clip> {
+        self.clips().find_map(|clip| {
+            if clip.id() == id {
+                Some(clip)
+            } else {
+                clip.find_clip(id)
+            }
+        })
+    }
+
+    pub fn find_clip_mut(&mut self, id: ClipId) -> Option<&mut Clip> {
+        self.clips_mut().find_map(|clip| {
+            if clip.id() == id {
+                Some(clip)
+            } else {
+                clip.find_clip_mut(id)
+            }
+        })
+    }
+
+    fn find_editable_siblings_mut(&mut self, id: ClipId) -> Option<(&mut Vec<Clip>, usize)> {
+        for track in self.tracks.iter_mut().filter(|track| !track.locked()) {
+            if let Some(found) = find_siblings_mut(track.clips_vec_mut(), id) {
+                return Some(found);
+            }
+        }
+
+        None
+    }
+
+    pub(crate) fn find_editable_clip_mut(&mut self, id: ClipId) -> Option<&mut Clip> {
+        let (siblings, index) = self.find_editable_siblings_mut(id)?;
+        Some(&mut siblings[index])
+    }
+
+    fn editable_children_mut(&mut self, parent: ClipParent) -> Option<&mut Vec<Clip>> {
+        match parent {
+            ClipParent::Track(id) => self
+                .track_mut(id)
+                .filter(|track| !track.locked())
+                .map(|track| track.clips_vec_mut()),
+            ClipParent::Clip(id) => self
+                .find_editable_clip_mut(id)
+                .map(|clip| clip.children_vec_mut()),
+        }
+    }
+
+    /// Drop the transitions to and from the clip with `id`, it left its track
+    fn remove_transitions(&mut self, id: ClipId) {
+        for track in &mut self.tracks {
+            track
+                .transitions_mut()
+                .retain(|transition| transition.from != id && transition.to != id);
+        }
+    }
+
+    /// Remove a clip (and its children, and its transitions), leaving a gap where it was
+    pub fn remove_clip(&mut self, id: ClipId) -> Option<Clip> {
+        let (siblings, index) = self.find_editable_siblings_mut(id)?;
+        let clip = siblings.remove(index);
+        self.remove_transitions(id);
+        Some(clip)
+    }
+
+    /// Point the transitions to and from the clip with `id` at the clip with `new_id`
+    fn retarget_transitions(&mut self, id: ClipId, new_id: ClipId, from: bool, to: bool) {
+        for track in &mut self.tracks {
+            for transition in track.transitions_mut() {
+                if from && transition.from == id {
+                    transition.from = new_id;
+                }
+                if to && transition.to == id {
+                    transition.to = new_id;
+                }
+            }
+        }
+    }
+
+    /// Put `clip` in the place of the clip with `id`, returning the replaced clip. Its
+    /// transitions move over to `clip`.
+    pub fn replace_clip(&mut self, id: ClipId, clip: Clip) -> Option<Clip> {
+        let new_id = clip.id();
+        let (siblings, index) = self.find_editable_siblings_mut(id)?;
+        let replaced = std::mem::replace(&mut siblings[index], clip);
+        self.retarget_transitions(id, new_id, true, true);
+        Some(replaced)
+    }
+
+    /// Split a clip in two at `at`, the second half (its content and its own
+    /// animation) continues where the first one stopped and gets new ids. Returns the id of the second half.
+    pub fn split_clip(&mut self, id: ClipId, at: TimeCode) -> Option<ClipId> {
+        let (siblings, index) = self.find_editable_siblings_mut(id)?;
+        let first = &mut siblings[index];
+        let range = first.range();
+        let start = range.start().unwrap_or_default();
+
+        if at <= start || range.end().is_some_and(|end| at >= end) {
+            return None;
+        }
+
+        let mut second = first.duplicate();
+        second.range_mut().set_start(Some(at));
+        second.range_mut().set_end(range.end());
+        second.set_content_offset(first.content_offset() + (at - start));
+        second.set_animation_offset(first.animation_offset() + (at - start));
+        first.range_mut().set_end(Some(at));
+
+        let second_id = second.id();
+        siblings.insert(index + 1, second);
+
+        // Transitions out of the clip now start at the end of the second half
+        self.retarget_transitions(id, second_id, true, false);
+
+        Some(second_id)
+    }
+
+    /// Move the start of a clip, keeping its content and animation in place
+    pub fn trim_in(&mut self, id: ClipId, start: TimeCode) -> bool {
+        let Some(clip) = self.find_editable_clip_mut(id) else {
+            return false;
+        };
+        let range = clip.range();
+
+        if range.end().is_some_and(|end| start >= end) {
+            return false;
+        }
+
+        let old_start = range.start().unwrap_or_default();
+        clip.set_content_offset(clip.content_offset() + (start - old_start));
+        clip.set_animation_offset(clip.animation_offset() + (start - old_start));
+        clip.range_mut().set_start(Some(start));
+        true
+    }
+
+    /// Move the end of a clip
+    pub fn trim_out(&mut self, id: ClipId, end: TimeCode) -> bool {
+        let Some(clip) = self.find_editable_clip_mut(id) else {
+            return false;
+        };
+
+        if end <= clip.range().start().unwrap_or_default() {
+            return false;
+        }
+
+        clip.range_mut().set_end(Some(end));
+        true
+    }
+
+    /// Shift the content of a clip by `offset` without moving the clip itself
+    pub fn slip_clip(&mut self, id: ClipId, offset: TimeCode) -> bool {
+        let Some(clip) = self.find_editable_clip_mut(id) else {
+            return false;
+        };
+
+        clip.set_content_offset(clip.content_offset() + offset);
+        true
+    }
+
+    /// Remove a clip and move every sibling after it back to close the gap
+    pub fn ripple_delete(&mut self, id: ClipId) -> Option<Clip> {
+        let (siblings, index) = self.find_editable_siblings_mut(id)?;
+        let clip = siblings.remove(index);
+        let range = clip.range();
+
+        if let (Some(duration), Some(end)) = (range.duration(), range.end()) {
+            ripple(siblings, end, TimeCode::new(-duration.value()));
+        }
+
+        self.remove_transitions(id);
+        Some(clip)
+    }
+
+    /// Add a clip to `parent`, moving every sibling that starts at or after it forward
+    /// to make room
+    pub fn insert_with_ripple(&mut self, parent: ClipParent, clip: Clip) -> bool {
+        let Some(siblings) = self.editable_children_mut(parent) else {
+            return false;
+        };
+
+        let range = clip.range();
+        if let Some(duration) = range.duration() {
+            ripple(siblings, range.start().unwrap_or_default(), duration);
+        }
+
+        siblings.push(clip);
+        true
+    }
+
+    /// Move a clip to `new_parent`, keeping its range
+    pub fn move_clip(&mut self, id: ClipId, new_parent: ClipParent) -> bool {
+        let Some(clip) = self.find_editable_clip_mut(id) else {
+            return false;
+        };
+
+        // Can't move a clip into itself
+        if let ClipParent::Clip(parent) = new_parent {
+            if parent == id || clip.find_clip(parent).is_some() {
+                return false;
+            }
+        }
+
+        if self.editable_children_mut(new_parent).is_none() {
+            return false;
+        }
+
+        // Transitions only stay valid if the clip stays directly on its track
+        let stays_on_track = matches!(new_parent, ClipParent::Track(track) if self
+            .track(track)
+            .is_some_and(|track| track.clips().iter().any(|clip| clip.id() == id)));
+
+        let Some((siblings, index)) = self.find_editable_siblings_mut(id) else {
+            return false;
+        };
+        let clip = siblings.remove(index);
+
+        if !stays_on_track {
+            self.remove_transitions(id);
+        }
+
+        self.editable_children_mut(new_parent)
+            .expect("parent was checked to be editable")
+            .push(clip);
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use vide_animate::{AnimatedProperty, KeyframeTiming};
+    use vide_common::{types::TimeUnit, FrameInfo};
+
+    use super::*;
+    use crate::{
+        track::{Track, TrackId},
+        transition::{Transition, TransitionKind},
+        validate::DiagnosticKind,
+    };
+
+    fn frame_info(time_code: TimeCode) -> FrameInfo {
+        FrameInfo {
+            time_code,
+            progress: 0.0,
+            resolution: (1920, 1080),
+        }
+    }
+
+    /// A project with a single 2 second clip that fades in and rotates
+    fn animated_project() -> (Project, ClipId) {
+        let mut clip = Clip::new(0.0..2.0);
+        clip.set_opacity(
+            AnimatedProperty::builder()
+                .keyframe(KeyframeTiming::Abs(TimeUnit::Seconds(0.0)), 0.0)
+                .keyframe(KeyframeTiming::Abs(TimeUnit::Seconds(2.0)), 1.0)
+                .build(),
+        );
+        clip.set_rotation(
+            AnimatedProperty::builder()
+                .keyframe(KeyframeTiming::Abs(TimeUnit::Seconds(0.0)), 0.0)
+                .keyframe(KeyframeTiming::Abs(TimeUnit::Seconds(2.0)), 90.0)
+                .build(),
+        );
+
+        let id = clip.id();
+        let mut project = Project::new();
+        project.add_clip(clip);
+
+        (project, id)
+    }
+
+    #[test]
+    fn split_keeps_animation_continuous() {
+        let (mut project, id) = animated_project();
+        let original = project.find_clip(id).unwrap().clone();
+
+        let second = project.split_clip(id, TimeCode::from_seconds(1.0)).unwrap();
+        let first = project.find_clip(id).unwrap();
+        let second = project.find_clip(second).unwrap();
+
+        assert_eq!(first.range().end(), Some(TimeCode::from_seconds(1.0)));
+        assert_eq!(second.range().start(), Some(TimeCode::from_seconds(1.0)));
+
+        for time in [0.0, 0.5, 0.99] {
+            let local = frame_info(TimeCode::from_seconds(time));
+            assert_eq!(
+                first.opacity_at(local.time_code),
+                original.opacity_at(local.time_code)
+            );
+            assert_eq!(first.transform_at(&local), original.transform_at(&local));
+        }
+
+        // The second half starts at its own local time 0, 1 second into the original
+        for time in [0.0, 0.5, 1.0] {
+            let local = frame_info(TimeCode::from_seconds(time));
+            let original_local = frame_info(TimeCode::from_seconds(time + 1.0));
+            assert_eq!(
+                second.opacity_at(local.time_code),
+                original.opacity_at(original_local.time_code)
+            );
+            assert_eq!(
+                second.transform_at(&local),
+                original.transform_at(&original_local)
+            );
+        }
+
+        assert_eq!(second.opacity_at(TimeCode::from_seconds(0.0)), 0.5);
+    }
+
+    #[test]
+    fn trim_in_keeps_animation_in_place() {
+        let (mut project, id) = animated_project();
+        let original = project.find_clip(id).unwrap().clone();
+
+        assert!(project.trim_in(id, TimeCode::from_seconds(0.5)));
+        let trimmed = project.find_clip(id).unwrap();
+
+        assert_eq!(
+            trimmed.content_time(TimeCode::from_seconds(0.0)),
+            TimeCode::from_seconds(0.5)
+        );
+        assert_eq!(
+            trimmed.opacity_at(TimeCode::from_seconds(0.0)),
+            original.opacity_at(TimeCode::from_seconds(0.5))
+        );
+        assert_eq!(
+            trimmed.transform_at(&frame_info(TimeCode::from_seconds(1.0))),
+            original.transform_at(&frame_info(TimeCode::from_seconds(1.5)))
+        );
+    }
+
+    #[test]
+    fn split_and_trimmed_clips_validate() {
+        let (mut project, id) = animated_project();
+        assert!(project.validate().is_empty());
+
+        project.split_clip(id, TimeCode::from_seconds(1.0)).unwrap();
+        assert!(project.validate().is_empty());
+
+        let (mut project, id) = animated_project();
+        assert!(project.trim_in(id, TimeCode::from_seconds(0.5)));
+        assert!(project.validate().is_empty());
+
+        // Without a continuation, keyframes after the end are still reported
+        let (mut project, id) = animated_project();
+        assert!(project.trim_out(id, TimeCode::from_seconds(1.0)));
+        let diagnostics = project.validate();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics[0].kind,
+            crate::validate::DiagnosticKind::KeyframePastEnd
+        );
+    }
+
+    /// A track with three 1 second clips back to back and transitions between them
+    fn sequence() -> (Project, TrackId, [ClipId; 3]) {
+        let mut track = Track::video("Video");
+        let clips = [0.0, 1.0, 2.0].map(|start| {
+            let clip = Clip::new(start..start + 1.0);
+            let id = clip.id();
+            track.add_clip(clip);
+            id
+        });
+
+        for pair in clips.windows(2) {
+            track.add_transition(Transition::new(
+                TransitionKind::Crossfade,
+                pair[0],
+                pair[1],
+                TimeCode::from_seconds(0.5),
+            ));
+        }
+
+        let mut project = Project::new();
+        let track_id = project.add_track(track);
+        (project, track_id, clips)
+    }
+
+    fn starts(project: &Project, track: TrackId) -> Vec<TimeCode> {
+        let track = project.track(track).unwrap();
+        let mut starts = track
+            .clips()
+            .iter()
+            .filter_map(|clip| clip.range().start())
+            .collect::<Vec<_>>();
+        starts.sort();
+        starts
+    }
+
+    fn transitions(project: &Project, track: TrackId) -> Vec<(ClipId, ClipId)> {
+        let track = project.track(track).unwrap();
+        track
+            .transitions()
+            .iter()
+            .map(|transition| (transition.from, transition.to))
+            .collect()
+    }
+
+    #[test]
+    fn split_moves_outgoing_transitions_to_the_second_half() {
+        let (mut project, track, [a, b, c]) = sequence();
+
+        let second = project.split_clip(b, TimeCode::from_seconds(1.5)).unwrap();
+
+        assert_eq!(transitions(&project, track), [(a, b), (second, c)]);
+        assert!(project.validate().is_empty());
+    }
+
+    #[test]
+    fn ripple_delete_closes_the_gap() {
+        let (mut project, track, [a, b, c]) = sequence();
+
+        let removed = project.ripple_delete(b).unwrap();
+
+        assert_eq!(removed.id(), b);
+        assert_eq!(
+            starts(&project, track),
+            [TimeCode::from_seconds(0.0), TimeCode::from_seconds(1.0)]
+        );
+        assert_eq!(
+            project.find_clip(c).unwrap().range().end(),
+            Some(TimeCode::from_seconds(2.0))
+        );
+        assert!(transitions(&project, track).is_empty());
+        assert!(project.validate().is_empty());
+        assert!(project.find_clip(a).is_some());
+    }
+
+    #[test]
+    fn insert_with_ripple_makes_room() {
+        let (mut project, track, [a, b, c]) = sequence();
+
+        assert!(project.insert_with_ripple(ClipParent::Track(track), Clip::new(1.0..1.5)));
+
+        assert_eq!(
+            starts(&project, track),
+            [
+                TimeCode::from_seconds(0.0),
+                TimeCode::from_seconds(1.0),
+                TimeCode::from_seconds(1.5),
+                TimeCode::from_seconds(2.5)
+            ]
+        );
+        assert_eq!(
+            project.find_clip(a).unwrap().range().start(),
+            Some(TimeCode::from_seconds(0.0))
+        );
+        assert_eq!(
+            project.find_clip(b).unwrap().range().start(),
+            Some(TimeCode::from_seconds(1.5))
+        );
+        assert_eq!(
+            project.find_clip(c).unwrap().range().start(),
+            Some(TimeCode::from_seconds(2.5))
+        );
+    }
+
+    #[test]
+    fn move_clip_between_parents() {
+        let (mut project, track, [a, b, c]) = sequence();
+
+        // Staying on the track keeps the transitions
+        assert!(project.move_clip(c, ClipParent::Track(track)));
+        assert_eq!(transitions(&project, track), [(a, b), (b, c)]);
+
+        // Can't move a clip into itself
+        assert!(!project.move_clip(b, ClipParent::Clip(b)));
+
+        assert!(project.move_clip(c, ClipParent::Clip(a)));
+        let parent = project.find_clip(a).unwrap();
+        assert_eq!(parent.children().len(), 1);
+        assert_eq!(parent.children()[0].id(), c);
+        assert_eq!(
+            parent.children()[0].range().start(),
+            Some(TimeCode::from_seconds(2.0))
+        );
+        assert_eq!(transitions(&project, track), [(a, b)]);
+
+        // Or into one of its children
+        assert!(!project.move_clip(a, ClipParent::Clip(c)));
+
+        let other = project.add_track(Track::video("Other"));
+        assert!(project.move_clip(b, ClipParent::Track(other)));
+        assert_eq!(project.track(other).unwrap().clips()[0].id(), b);
+        assert!(transitions(&project, track).is_empty());
+        assert!(project
+            .validate()
+            .iter()
+            .all(|diagnostic| diagnostic.kind != DiagnosticKind::MissingTransitionClip));
+    }
+
+    #[test]
+    fn replace_clip_keeps_transitions() {
+        let (mut project, track, [a, b, c]) = sequence();
+
+        let replacement = Clip::new(1.0..2.0);
+        let new_id = replacement.id();
+        let replaced = project.replace_clip(b, replacement).unwrap();
+
+        assert_eq!(replaced.id(), b);
+        assert!(project.find_clip(b).is_none());
+        assert_eq!(transitions(&project, track), [(a, new_id), (new_id, c)]);
+        assert!(project.replace_clip(b, Clip::new(0.0..1.0)).is_none());
+    }
+
+    #[test]
+    fn locked_tracks_are_not_edited() {
+        let (mut project, track, [_, b, _]) = sequence();
+        project.track_mut(track).unwrap().set_locked(true);
+
+        assert!(project.ripple_delete(b).is_none());
+        assert!(project.split_clip(b, TimeCode::from_seconds(1.5)).is_none());
+        assert!(!project.insert_with_ripple(ClipParent::Track(track), Clip::new(0.0..1.0)));
+    }
+
+    #[test]
+    fn slip_only_moves_content() {
+        let (mut project, id) = animated_project();
+        let original = project.find_clip(id).unwrap().clone();
+
+        assert!(project.slip_clip(id, TimeCode::from_seconds(1.0)));
+        let slipped = project.find_clip(id).unwrap();
+
+        assert_eq!(
+            slipped.content_time(TimeCode::from_seconds(0.0)),
+            TimeCode::from_seconds(1.0)
+        );
+        assert_eq!(
+            slipped.opacity_at(TimeCode::from_seconds(0.5)),
+            original.opacity_at(TimeCode::from_seconds(0.5))
+        );
+    }
+}
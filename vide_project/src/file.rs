@@ -0,0 +1,141 @@
+//! Saving and loading projects as JSON. Visible objects, effects and easings are
+//! written with the type tag they were registered under, see
+//! [`register_visible_object`](vide_common::visible_object::register_visible_object).
+
+use std::{fs, io, path::Path};
+
+use serde::{de::Error, Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::Project;
+
+/// Version of the project format, increased whenever a change would break older files
+pub const SCHEMA_VERSION: u32 = 1;
+
+#[derive(Serialize)]
+struct ProjectFile<'a> {
+    version: u32,
+    project: &'a Project,
+}
+
+#[derive(Deserialize)]
+struct LoadedProjectFile {
+    version: u32,
+    project: Value,
+}
+
+impl Project {
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(&ProjectFile {
+            version: SCHEMA_VERSION,
+            project: self,
+        })
+    }
+
+    /// Fails if the file was written by a newer version of the format
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        let file: LoadedProjectFile = serde_json::from_str(json)?;
+
+        if file.version > SCHEMA_VERSION {
+            return Err(serde_json::Error::custom(format!(
+                "project has schema version {}, only up to {SCHEMA_VERSION} is supported",
+                file.version
+            )));
+        }
+
+        serde_json::from_value(file.project)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, self.to_json()?)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        Ok(Self::from_json(&fs::read_to_string(path)?)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use vide_animate::{cubic_bezier::consts::EASE_IN_OUT_CUBIC, AnimatedProperty, KeyframeTiming};
+    use vide_common::types::TimeUnit;
+
+    use super::*;
+    use crate::{clip::Clip, marker::Marker};
+
+    fn project() -> Project {
+        let mut clip = Clip::new(0.0..2.0);
+        clip.set_opacity(
+            AnimatedProperty::builder()
+                .keyframe(KeyframeTiming::Abs(TimeUnit::Seconds(0.0)), 0.0)
+                .keyframe_ease(
+                    KeyframeTiming::Abs(TimeUnit::Seconds(1.0)),
+                    1.0,
+                    EASE_IN_OUT_CUBIC,
+                )
+                .build(),
+        );
+        clip.add_clip(Clip::new(0.5..1.5));
+
+        let mut project = Project::new();
+        project.add_clip(clip);
+        project.add_marker(Marker::chapter("Intro", TimeUnit::Seconds(0.0)));
+        project
+    }
+
+    #[test]
+    fn round_trip() {
+        let project = project();
+        let json = project.to_json().unwrap();
+        let loaded = Project::from_json(&json).unwrap();
+
+        assert_eq!(loaded.to_json().unwrap(), json);
+
+        let clip = loaded.clips().next().unwrap();
+        let time_code = TimeUnit::Seconds(0.25).into();
+        assert_eq!(
+            clip.opacity_at(time_code),
+            project.clips().next().unwrap().opacity_at(time_code)
+        );
+        assert_eq!(loaded.chapters(), project.chapters());
+    }
+
+    #[test]
+    fn save_and_load() {
+        let path = std::env::temp_dir().join(format!("vide-file-test-{}.json", std::process::id()));
+        let project = project();
+
+        project.save(&path).unwrap();
+        let loaded = Project::load(&path);
+        let _ = fs::remove_file(&path);
+
+        assert_eq!(
+            loaded.unwrap().to_json().unwrap(),
+            project.to_json().unwrap()
+        );
+    }
+
+    #[test]
+    fn rejects_newer_versions() {
+        let mut file: Value = serde_json::from_str(&project().to_json().unwrap()).unwrap();
+        file["version"] = (SCHEMA_VERSION + 1).into();
+
+        let error = Project::from_json(&file.to_string()).unwrap_err();
+        assert!(error.to_string().contains(&format!(
+            "project has schema version {}, only up to {SCHEMA_VERSION} is supported",
+            SCHEMA_VERSION + 1
+        )));
+    }
+
+    #[test]
+    fn rejects_unregistered_types() {
+        let mut file: Value = serde_json::from_str(&project().to_json().unwrap()).unwrap();
+        file["project"]["tracks"][0]["clips"][0]["video_source"] =
+            serde_json::json!({ "type": "Unregistered", "value": {} });
+
+        let error = Project::from_json(&file.to_string()).unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("unknown type `Unregistered`, it might not be registered"));
+    }
+}
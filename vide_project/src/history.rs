@@ -0,0 +1,506 @@
+//! Undo and redo. Every change to a project is made through a [`Command`] that knows
+//! how to revert itself, [`History`] keeps track of them.
+
+use std::any::Any;
+
+use crate::{
+    clip::{Clip, ClipId},
+    Project,
+};
+
+/// What a command changed, so previews only have to re-render that
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Change {
+    /// A clip (and possibly its children)
+    Clip(ClipId),
+    /// Anything in the project
+    Project,
+}
+
+/// A reversible change to a project
+pub trait Command: Any {
+    /// Make the change, returns `false` (without changing anything) if it can't be made
+    fn apply(&mut self, project: &mut Project) -> bool;
+
+    /// Undo the change, only called after [`Command::apply`] succeeded. Returns `false`
+    /// (without changing anything) if it can't be undone, for example because the clip
+    /// is on a locked track now.
+    fn revert(&mut self, project: &mut Project) -> bool;
+
+    /// Shown in undo/redo menus
+    fn name(&self) -> &str;
+
+    fn change(&self) -> Change;
+
+    /// Absorb `next`, which was applied right after this command, so both are undone at
+    /// once. Used for continuous edits like dragging a clip. Returns `false` if the
+    /// commands can't be merged.
+    fn merge(&mut self, _next: &mut dyn Command) -> bool {
+        false
+    }
+}
+
+type ClipEdit = Box<dyn FnOnce(&mut Clip)>;
+
+/// Change a clip by running `edit` on it, reverting restores a snapshot of the clip
+/// (including its children). Consecutive edits of the same clip with the same name
+/// are merged.
+pub struct EditClip {
+    name: String,
+    id: ClipId,
+    edit: Option<ClipEdit>,
+    before: Option<Clip>,
+    after: Option<Clip>,
+}
+
+impl EditClip {
+    pub fn new(
+        name: impl Into<String>,
+        id: ClipId,
+        edit: impl FnOnce(&mut Clip) + 'static,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            id,
+            edit: Some(Box::new(edit)),
+            before: None,
+            after: None,
+        }
+    }
+}
+
+impl Command for EditClip {
+    fn apply(&mut self, project: &mut Project) -> bool {
+        if let Some(after) = &self.after {
+            return project.replace_clip(self.id, after.clone()).is_some();
+        }
+
+        let Some(clip) = project.find_editable_clip_mut(self.id) else {
+            return false;
+        };
+
+        self.before = Some(clip.clone());
+        (self.edit.take().expect("edit is only run once"))(clip);
+        self.after = Some(clip.clone());
+
+        true
+    }
+
+    fn revert(&mut self, project: &mut Project) -> bool {
+        let before = self.before.clone().expect("command was applied");
+        project.replace_clip(self.id, before).is_some()
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn change(&self) -> Change {
+        Change::Clip(self.id)
+    }
+
+    fn merge(&mut self, next: &mut dyn Command) -> bool {
+        let Some(next) = (next as &mut dyn Any).downcast_mut::<Self>() else {
+            return false;
+        };
+
+        if next.id != self.id || next.name != self.name {
+            return false;
+        }
+
+        self.after = next.after.take();
+        true
+    }
+}
+
+type ProjectEdit = Box<dyn FnOnce(&mut Project) -> bool>;
+
+/// Change anything in a project by running `edit` on it, reverting restores a
+/// snapshot of the whole project. `edit` returns `false` if it failed, for example
+/// `|project| project.split_clip(id, at).is_some()`.
+pub struct EditProject {
+    name: String,
+    edit: Option<ProjectEdit>,
+    before: Option<Project>,
+    after: Option<Project>,
+}
+
+impl EditProject {
+    pub fn new(name: impl Into<String>, edit: impl FnOnce(&mut Project) -> bool + 'static) -> Self {
+        Self {
+            name: name.into(),
+            edit: Some(Box::new(edit)),
+            before: None,
+            after: None,
+        }
+    }
+}
+
+impl Command for EditProject {
+    fn apply(&mut self, project: &mut Project) -> bool {
+        if let Some(after) = &self.after {
+            *project = after.clone();
+            return true;
+        }
+
+        let before = project.clone();
+        if !(self.edit.take().expect("edit is only run once"))(project) {
+            *project = before;
+            return false;
+        }
+
+        self.before = Some(before);
+        self.after = Some(project.clone());
+
+        true
+    }
+
+    fn revert(&mut self, project: &mut Project) -> bool {
+        *project = self.before.clone().expect("command was applied");
+        true
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn change(&self) -> Change {
+        Change::Project
+    }
+}
+
+type Listener = Box<dyn FnMut(&Project, Change)>;
+
+/// The undo and redo stacks of a project
+pub struct History {
+    undo: Vec<Box<dyn Command>>,
+    redo: Vec<Box<dyn Command>>,
+    listeners: Vec<Listener>,
+    limit: usize,
+    merging: bool,
+}
+
+impl History {
+    pub fn new() -> Self {
+        Self {
+            undo: Vec::new(),
+            redo: Vec::new(),
+            listeners: Vec::new(),
+            limit: 100,
+            merging: true,
+        }
+    }
+
+    /// How many commands can be undone, older ones are dropped. Defaults to 100.
+    pub fn set_limit(&mut self, limit: usize) {
+        self.limit = limit;
+        self.trim();
+    }
+
+    /// Call `listener` after every change made by executing, undoing or redoing a command
+    pub fn on_change(&mut self, listener: impl FnMut(&Project, Change) + 'static) {
+        self.listeners.push(Box::new(listener));
+    }
+
+    /// Apply `command` and put it on the undo stack, clearing the redo stack. Returns
+    /// `false` if the command couldn't be applied.
+    pub fn execute(&mut self, project: &mut Project, mut command: impl Command) -> bool {
+        if !command.apply(project) {
+            return false;
+        }
+
+        self.redo.clear();
+        self.notify(project, command.change());
+
+        let merged = self.merging
+            && self
+                .undo
+                .last_mut()
+                .is_some_and(|last| last.merge(&mut command));
+
+        if !merged {
+            self.undo.push(Box::new(command));
+            self.trim();
+        }
+
+        self.merging = true;
+        true
+    }
+
+    /// Don't merge the next command into the last one, for example when the user lets
+    /// go of a slider
+    pub fn end_merge(&mut self) {
+        self.merging = false;
+    }
+
+    pub fn undo(&mut self, project: &mut Project) -> bool {
+        let Some(mut command) = self.undo.pop() else {
+            return false;
+        };
+
+        // The project was changed outside of the history, so nothing can be undone
+        if !command.revert(project) {
+            self.undo.clear();
+            return false;
+        }
+
+        self.notify(project, command.change());
+        self.redo.push(command);
+        self.merging = false;
+
+        true
+    }
+
+    pub fn redo(&mut self, project: &mut Project) -> bool {
+        let Some(mut command) = self.redo.pop() else {
+            return false;
+        };
+
+        // The project was changed outside of the history, so nothing can be redone
+        if !command.apply(project) {
+            self.redo.clear();
+            return false;
+        }
+
+        self.notify(project, command.change());
+        self.undo.push(command);
+        self.merging = false;
+
+        true
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Name of the command [`History::undo`] would revert
+    pub fn undo_name(&self) -> Option<&str> {
+        self.undo.last().map(|command| command.name())
+    }
+
+    /// Name of the command [`History::redo`] would apply
+    pub fn redo_name(&self) -> Option<&str> {
+        self.redo.last().map(|command| command.name())
+    }
+
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+
+    fn trim(&mut self) {
+        if self.undo.len() > self.limit {
+            self.undo.drain(..self.undo.len() - self.limit);
+        }
+    }
+
+    fn notify(&mut self, project: &Project, change: Change) {
+        for listener in &mut self.listeners {
+            listener(project, change);
+        }
+    }
+}
+
+impl Default for History {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use vide_common::prelude::TimeCode;
+
+    use super::*;
+
+    /// A project with a single clip named "0"
+    fn project() -> (Project, ClipId) {
+        let mut clip = Clip::new(0.0..1.0);
+        clip.set_name("0");
+        let id = clip.id();
+
+        let mut project = Project::new();
+        project.add_clip(clip);
+
+        (project, id)
+    }
+
+    fn name(project: &Project, id: ClipId) -> &str {
+        project.find_clip(id).unwrap().name()
+    }
+
+    fn rename(id: ClipId, name: &'static str) -> EditClip {
+        EditClip::new("Rename", id, move |clip| clip.set_name(name))
+    }
+
+    #[test]
+    fn undo_and_redo() {
+        let (mut project, id) = project();
+        let mut history = History::new();
+
+        let changes = Rc::new(RefCell::new(Vec::new()));
+        let listener = changes.clone();
+        history.on_change(move |_, change| listener.borrow_mut().push(change));
+
+        assert!(history.execute(&mut project, rename(id, "1")));
+        assert_eq!(name(&project, id), "1");
+        assert_eq!(history.undo_name(), Some("Rename"));
+        assert!(!history.can_redo());
+
+        assert!(history.undo(&mut project));
+        assert_eq!(name(&project, id), "0");
+        assert!(!history.can_undo());
+        assert_eq!(history.redo_name(), Some("Rename"));
+        assert!(!history.undo(&mut project));
+
+        assert!(history.redo(&mut project));
+        assert_eq!(name(&project, id), "1");
+        assert!(!history.redo(&mut project));
+
+        assert_eq!(*changes.borrow(), [Change::Clip(id); 3]);
+    }
+
+    #[test]
+    fn execute_clears_redo() {
+        let (mut project, id) = project();
+        let mut history = History::new();
+
+        history.execute(&mut project, rename(id, "1"));
+        history.undo(&mut project);
+        assert!(history.can_redo());
+
+        history.execute(
+            &mut project,
+            EditClip::new("Move", id, |clip| clip.set_range(1.0..2.0)),
+        );
+        assert!(!history.can_redo());
+
+        history.undo(&mut project);
+        assert_eq!(name(&project, id), "0");
+        assert!(!history.can_undo());
+    }
+
+    #[test]
+    fn merge() {
+        let (mut project, id) = project();
+        let mut history = History::new();
+
+        // Consecutive edits with the same name are undone at once
+        history.execute(&mut project, rename(id, "1"));
+        history.execute(&mut project, rename(id, "2"));
+        history.execute(&mut project, rename(id, "3"));
+        assert_eq!(name(&project, id), "3");
+
+        history.undo(&mut project);
+        assert_eq!(name(&project, id), "0");
+        assert!(!history.can_undo());
+
+        history.redo(&mut project);
+        assert_eq!(name(&project, id), "3");
+
+        // Not after ending the merge, undoing or redoing
+        history.execute(&mut project, rename(id, "4"));
+        history.end_merge();
+        history.execute(&mut project, rename(id, "5"));
+
+        history.undo(&mut project);
+        assert_eq!(name(&project, id), "4");
+        history.undo(&mut project);
+        assert_eq!(name(&project, id), "3");
+        history.undo(&mut project);
+        assert_eq!(name(&project, id), "0");
+
+        // Or with a different name
+        history.execute(&mut project, rename(id, "1"));
+        history.execute(
+            &mut project,
+            EditClip::new("Disable", id, |clip| clip.set_enabled(false)),
+        );
+
+        history.undo(&mut project);
+        assert_eq!(name(&project, id), "1");
+        assert!(project.find_clip(id).unwrap().enabled());
+    }
+
+    #[test]
+    fn limit() {
+        let (mut project, id) = project();
+        let mut history = History::new();
+
+        for name in ["1", "2", "3", "4"] {
+            history.execute(&mut project, rename(id, name));
+            history.end_merge();
+        }
+
+        history.set_limit(2);
+
+        assert!(history.undo(&mut project));
+        assert!(history.undo(&mut project));
+        assert!(!history.undo(&mut project));
+        assert_eq!(name(&project, id), "2");
+
+        history.redo(&mut project);
+        history.redo(&mut project);
+        history.execute(&mut project, rename(id, "5"));
+        assert_eq!(name(&project, id), "5");
+
+        history.undo(&mut project);
+        history.undo(&mut project);
+        assert!(!history.can_undo());
+        assert_eq!(name(&project, id), "3");
+    }
+
+    #[test]
+    fn failed_commands() {
+        let (mut project, id) = project();
+        let mut history = History::new();
+
+        let changes = Rc::new(RefCell::new(0));
+        let listener = changes.clone();
+        history.on_change(move |_, _| *listener.borrow_mut() += 1);
+
+        assert!(!history.execute(&mut project, EditProject::new("Nothing", |_| false)));
+        assert!(!history.can_undo());
+
+        history.execute(&mut project, rename(id, "1"));
+
+        // Undoing an edit of a clip on a locked track doesn't change anything
+        project.tracks_mut()[0].set_locked(true);
+        assert!(!history.undo(&mut project));
+        assert_eq!(name(&project, id), "1");
+        assert!(!history.can_undo());
+        assert!(!history.can_redo());
+
+        assert_eq!(*changes.borrow(), 1);
+    }
+
+    #[test]
+    fn edit_project() {
+        let (mut project, id) = project();
+        let mut history = History::new();
+
+        assert!(history.execute(
+            &mut project,
+            EditProject::new("Split", move |project| project
+                .split_clip(id, TimeCode::new(30_000))
+                .is_some()),
+        ));
+        assert_eq!(project.clips().count(), 2);
+
+        history.undo(&mut project);
+        assert_eq!(project.clips().count(), 1);
+        assert_eq!(
+            project.find_clip(id).unwrap().range().end(),
+            Some(TimeCode::new(60_000))
+        );
+
+        history.redo(&mut project);
+        assert_eq!(project.clips().count(), 2);
+    }
+}
@@ -1,45 +1,244 @@
+use canvas::Canvas;
 use clip::Clip;
+use marker::Marker;
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+use track::{Track, TrackId, TrackKind};
+
 use vide_common::{
+    config::RenderRange,
     prelude::TimeCode,
+    time_code::UnboundedTimecodeRange,
     types::{Frames, FramesPerSecond, TimeUnit},
 };
 
+pub mod canvas;
 pub mod clip;
+mod edit;
+pub mod file;
+pub mod history;
+pub mod marker;
+pub mod matte;
 pub mod prelude;
+pub mod time_remap;
+pub mod track;
+pub mod transition;
+pub mod validate;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Project {
-    clips: Vec<Clip>,
+    tracks: Vec<Track>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    duration: Option<TimeCode>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    work_area: Option<UnboundedTimecodeRange>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    markers: Vec<Marker>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    canvas: Option<Canvas>,
 }
 
 impl Project {
     pub fn new() -> Self {
-        Self { clips: Vec::new() }
+        Self {
+            tracks: Vec::new(),
+            duration: None,
+            work_area: None,
+            markers: Vec::new(),
+            canvas: None,
+        }
+    }
+
+    pub fn tracks(&self) -> &[Track] {
+        &self.tracks
+    }
+
+    pub fn tracks_mut(&mut self) -> &mut [Track] {
+        &mut self.tracks
+    }
+
+    pub fn add_track(&mut self, track: Track) -> TrackId {
+        let id = track.id();
+        self.tracks.push(track);
+        id
+    }
+
+    pub fn track(&self, id: TrackId) -> Option<&Track> {
+        self.tracks.iter().find(|track| track.id() == id)
     }
 
-    pub fn clips(&self) -> &[Clip] {
-        &self.clips
+    pub fn track_mut(&mut self, id: TrackId) -> Option<&mut Track> {
+        self.tracks.iter_mut().find(|track| track.id() == id)
     }
 
-    pub fn clips_mut(&mut self) -> &mut [Clip] {
-        &mut self.clips
+    pub fn remove_track(&mut self, id: TrackId) -> Option<Track> {
+        let index = self.tracks.iter().position(|track| track.id() == id)?;
+        Some(self.tracks.remove(index))
     }
 
+    /// The track [`Project::add_clip`] adds to, the first video track that isn't a guide.
+    /// One is created if it doesn't exist yet.
+    pub fn default_track_mut(&mut self) -> &mut Track {
+        let index = match self
+            .tracks
+            .iter()
+            .position(|track| track.kind() == TrackKind::Video && !track.guide())
+        {
+            Some(index) => index,
+            None => {
+                self.tracks.push(Track::video("Video 1"));
+                self.tracks.len() - 1
+            }
+        };
+
+        &mut self.tracks[index]
+    }
+
+    /// All top-level clips, in all tracks
+    pub fn clips(&self) -> impl Iterator<Item = &Clip> {
+        self.tracks.iter().flat_map(|track| track.clips())
+    }
+
+    pub fn clips_mut(&mut self) -> impl Iterator<Item = &mut Clip> {
+        self.tracks.iter_mut().flat_map(|track| track.clips_mut())
+    }
+
+    /// Add a clip to the default track
     pub fn add_clip(&mut self, clip: Clip) {
-        self.clips.push(clip);
+        self.default_track_mut().add_clip(clip);
     }
 
-    pub fn duration(&self) -> TimeCode {
-        self.clips
+    /// Whether `track` ends up in a render, `any_solo` is whether any video track is solo
+    fn is_rendered(track: &Track, any_solo: bool) -> bool {
+        track.kind() == TrackKind::Video
+            && track.enabled()
+            && !track.guide()
+            && (track.solo() || !any_solo)
+    }
+
+    fn any_solo(&self) -> bool {
+        self.tracks
             .iter()
-            .filter_map(|clip| clip.range().end())
-            .max()
-            .unwrap_or(TimeUnit::Seconds(5.0).into())
+            .any(|track| track.kind() == TrackKind::Video && track.solo())
+    }
+
+    /// The video tracks that end up in a render, from the bottom to the top
+    pub fn rendered_tracks(&self) -> Vec<&Track> {
+        let any_solo = self.any_solo();
+
+        let mut tracks = self
+            .tracks
+            .iter()
+            .filter(|track| Self::is_rendered(track, any_solo))
+            .collect::<Vec<_>>();
+
+        tracks.sort_by_key(|track| track.z_index());
+        tracks
+    }
+
+    /// See [`Project::rendered_tracks`]
+    pub fn rendered_tracks_mut(&mut self) -> Vec<&mut Track> {
+        let any_solo = self.any_solo();
+
+        let mut tracks = self
+            .tracks
+            .iter_mut()
+            .filter(|track| Self::is_rendered(track, any_solo))
+            .collect::<Vec<_>>();
+
+        tracks.sort_by_key(|track| track.z_index());
+        tracks
+    }
+
+    /// Give every clip without an end the duration of its content, unless the clip
+    /// opts out with [`DurationInference::Off`](clip::DurationInference::Off).
+    /// Rendering does this first.
+    pub fn infer_durations(&mut self) {
+        self.clips_mut().for_each(Clip::infer_durations);
+    }
+
+    /// The explicit duration if it is set, otherwise the end of the last clip on a
+    /// rendered track (or 5 seconds if no such clip has an end)
+    pub fn duration(&self) -> TimeCode {
+        self.duration_with(|clip| clip.range().end())
+    }
+
+    /// [`Project::duration`] as it will be after [`Project::infer_durations`], without
+    /// modifying the project
+    pub fn inferred_duration(&self) -> TimeCode {
+        self.duration_with(Clip::inferred_end)
+    }
+
+    fn duration_with(&self, end: impl Fn(&Clip) -> Option<TimeCode>) -> TimeCode {
+        self.duration.unwrap_or_else(|| {
+            self.rendered_tracks()
+                .into_iter()
+                .flat_map(|track| track.clips())
+                .filter_map(end)
+                .max()
+                .unwrap_or(TimeUnit::Seconds(5.0).into())
+        })
+    }
+
+    pub fn explicit_duration(&self) -> Option<TimeCode> {
+        self.duration
+    }
+
+    /// Fix the duration independent of the clips, `None` goes back to using the clips
+    pub fn set_duration(&mut self, duration: Option<TimeCode>) {
+        self.duration = duration;
+    }
+
+    /// The part of the project that is being worked on, see [`RenderRange::WorkArea`]
+    pub fn work_area(&self) -> Option<UnboundedTimecodeRange> {
+        self.work_area
+    }
+
+    pub fn set_work_area(&mut self, work_area: Option<UnboundedTimecodeRange>) {
+        self.work_area = work_area;
+    }
+
+    pub fn canvas(&self) -> Option<Canvas> {
+        self.canvas
+    }
+
+    /// Lay out clips on a canvas of a fixed size, instead of the output resolution
+    pub fn set_canvas(&mut self, canvas: Option<Canvas>) {
+        self.canvas = canvas;
     }
 
     pub fn frame_count(&self, frames_per_second: FramesPerSecond) -> Frames {
         (self.duration().seconds() * frames_per_second) as Frames
     }
+
+    /// The frames in `range`, limited to the duration of the project
+    pub fn frame_range(
+        &self,
+        range: RenderRange,
+        frames_per_second: FramesPerSecond,
+    ) -> Range<Frames> {
+        let frames = self.frame_count(frames_per_second);
+        let to_frame =
+            |time_code: TimeCode| (time_code.seconds() * frames_per_second).round() as Frames;
+
+        let time_range = match range {
+            RenderRange::Full => None,
+            RenderRange::WorkArea => self.work_area,
+            RenderRange::Time(time_range) => Some(time_range),
+            RenderRange::Frames { start, end } => {
+                return start.clamp(0, frames)..end.clamp(0, frames);
+            }
+        };
+
+        let Some(time_range) = time_range else {
+            return 0..frames;
+        };
+
+        let start = time_range.start().map_or(0, to_frame);
+        let end = time_range.end().map_or(frames, to_frame);
+
+        start.clamp(0, frames)..end.clamp(0, frames)
+    }
 }
 
 impl Default for Project {
@@ -47,3 +246,92 @@ impl Default for Project {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn duration_ignores_tracks_that_are_not_rendered() {
+        let mut project = Project::new();
+        project.add_clip(Clip::new(0.0..2.0));
+
+        let mut guide = Track::video("Guide");
+        guide.set_guide(true);
+        guide.add_clip(Clip::new(0.0..10.0));
+        project.add_track(guide);
+
+        let mut disabled = Track::video("Disabled");
+        disabled.set_enabled(false);
+        disabled.add_clip(Clip::new(0.0..8.0));
+        project.add_track(disabled);
+
+        let mut audio = Track::audio("Audio");
+        audio.add_clip(Clip::new(0.0..6.0));
+        project.add_track(audio);
+
+        assert_eq!(project.duration(), TimeCode::from_seconds(2.0));
+
+        let mut solo = Track::video("Solo");
+        solo.set_solo(true);
+        solo.add_clip(Clip::new(0.0..1.0));
+        project.add_track(solo);
+
+        assert_eq!(project.duration(), TimeCode::from_seconds(1.0));
+    }
+
+    #[test]
+    fn frame_range() {
+        // 10 seconds at 25 fps, 250 frames
+        let mut project = Project::new();
+        project.add_clip(Clip::new(0.0..10.0));
+
+        assert_eq!(project.frame_range(RenderRange::Full, 25.0), 0..250);
+        assert_eq!(
+            project.frame_range(RenderRange::Time((2.0..4.0).into()), 25.0),
+            50..100
+        );
+        assert_eq!(
+            project.frame_range(RenderRange::Time((8.0..).into()), 25.0),
+            200..250
+        );
+        assert_eq!(
+            project.frame_range(RenderRange::Time((..1.0).into()), 30.0),
+            0..30
+        );
+        assert_eq!(
+            project.frame_range(RenderRange::Frames { start: 10, end: 20 }, 25.0),
+            10..20
+        );
+
+        // Limited to the project
+        assert_eq!(
+            project.frame_range(RenderRange::Time((5.0..20.0).into()), 25.0),
+            125..250
+        );
+        assert_eq!(
+            project.frame_range(
+                RenderRange::Frames {
+                    start: -5,
+                    end: 1000
+                },
+                25.0
+            ),
+            0..250
+        );
+
+        // Ranges outside of the project or ending before they start are empty
+        assert!(project
+            .frame_range(RenderRange::Time((12.0..15.0).into()), 25.0)
+            .is_empty());
+        assert!(project
+            .frame_range(RenderRange::Frames { start: 20, end: 10 }, 25.0)
+            .is_empty());
+
+        // The work area falls back to everything
+        assert_eq!(project.frame_range(RenderRange::WorkArea, 25.0), 0..250);
+        project.set_work_area(Some((1.0..3.0).into()));
+        assert_eq!(project.frame_range(RenderRange::WorkArea, 25.0), 25..75);
+        assert_eq!(project.frame_range(RenderRange::Full, 25.0), 0..250);
+    }
+}
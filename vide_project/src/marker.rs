@@ -0,0 +1,319 @@
+//! Named points and ranges on the timeline. Chapter markers are written to the
+//! container and to sidecar files by exporters that support it.
+
+use std::{fs, io, path::Path};
+
+use serde::{Deserialize, Serialize};
+use vide_common::prelude::TimeCode;
+
+use crate::Project;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MarkerKind {
+    /// A note for editing, not exported
+    #[default]
+    Marker,
+    /// Starts a chapter that viewers can skip to
+    Chapter,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Marker {
+    pub name: String,
+    #[serde(default)]
+    pub kind: MarkerKind,
+    pub time: TimeCode,
+    /// Markers without a duration are a single point, chapters without one last
+    /// until the next chapter
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration: Option<TimeCode>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub comment: String,
+}
+
+impl Marker {
+    pub fn new(name: impl Into<String>, time: impl Into<TimeCode>) -> Self {
+        Self {
+            name: name.into(),
+            kind: MarkerKind::Marker,
+            time: time.into(),
+            duration: None,
+            comment: String::new(),
+        }
+    }
+
+    pub fn chapter(name: impl Into<String>, time: impl Into<TimeCode>) -> Self {
+        Self {
+            kind: MarkerKind::Chapter,
+            ..Self::new(name, time)
+        }
+    }
+
+    pub fn with_duration(mut self, duration: impl Into<TimeCode>) -> Self {
+        self.duration = Some(duration.into());
+        self
+    }
+
+    pub fn with_comment(mut self, comment: impl Into<String>) -> Self {
+        self.comment = comment.into();
+        self
+    }
+}
+
+/// A chapter marker with its end resolved, see [`Project::chapters`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Chapter {
+    pub title: String,
+    pub start: TimeCode,
+    pub end: TimeCode,
+    pub comment: String,
+}
+
+impl Project {
+    pub fn markers(&self) -> &[Marker] {
+        &self.markers
+    }
+
+    pub fn markers_mut(&mut self) -> &mut Vec<Marker> {
+        &mut self.markers
+    }
+
+    pub fn add_marker(&mut self, marker: Marker) {
+        self.markers.push(marker);
+    }
+
+    /// The chapter markers in order. Chapters without a duration end where the next one
+    /// starts, all of them end with the project at the latest.
+    pub fn chapters(&self) -> Vec<Chapter> {
+        let duration = self.duration();
+
+        let mut markers = self
+            .markers
+            .iter()
+            .filter(|marker| marker.kind == MarkerKind::Chapter && marker.time < duration)
+            .collect::<Vec<_>>();
+        markers.sort_by_key(|marker| marker.time);
+
+        markers
+            .iter()
+            .enumerate()
+            .map(|(index, marker)| {
+                let end = match marker.duration {
+                    Some(length) => marker.time + length,
+                    None => markers.get(index + 1).map_or(duration, |next| next.time),
+                };
+
+                Chapter {
+                    title: marker.name.clone(),
+                    start: marker.time,
+                    end: end.min(duration),
+                    comment: marker.comment.clone(),
+                }
+            })
+            .collect()
+    }
+
+    /// The [`Project::chapters`] that overlap `start..end`, cut to that range and moved
+    /// so `start` is at 0, for rendering part of a project
+    pub fn chapters_in(&self, start: TimeCode, end: TimeCode) -> Vec<Chapter> {
+        self.chapters()
+            .into_iter()
+            .filter(|chapter| chapter.end > start && chapter.start < end)
+            .map(|chapter| Chapter {
+                start: chapter.start.max(start) - start,
+                end: chapter.end.min(end) - start,
+                ..chapter
+            })
+            .collect()
+    }
+}
+
+/// `hh:mm:ss.mmm`
+fn webvtt_timestamp(time_code: TimeCode) -> String {
+    let milliseconds = (time_code.seconds() * 1000.0).round() as i64;
+
+    format!(
+        "{:02}:{:02}:{:02}.{:03}",
+        milliseconds / 3_600_000,
+        milliseconds / 60_000 % 60,
+        milliseconds / 1000 % 60,
+        milliseconds % 1000
+    )
+}
+
+/// A title on a single line without `-->`, either would end the cue early
+fn webvtt_text(title: &str) -> String {
+    title
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+        .replace("-->", "->")
+}
+
+/// Chapters as a WebVTT file, as used by HTML5 `<track kind="chapters">`
+pub fn chapters_webvtt(chapters: &[Chapter]) -> String {
+    let mut vtt = "WEBVTT\n".to_string();
+
+    for (index, chapter) in chapters.iter().enumerate() {
+        vtt += &format!(
+            "\n{}\n{} --> {}\n{}\n",
+            index + 1,
+            webvtt_timestamp(chapter.start),
+            webvtt_timestamp(chapter.end),
+            webvtt_text(&chapter.title)
+        );
+    }
+
+    vtt
+}
+
+/// Chapters as JSON, with times in seconds
+pub fn chapters_json(chapters: &[Chapter]) -> String {
+    let chapters = chapters
+        .iter()
+        .map(|chapter| {
+            serde_json::json!({
+                "title": chapter.title,
+                "start": chapter.start.seconds(),
+                "end": chapter.end.seconds(),
+                "comment": chapter.comment,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    serde_json::to_string_pretty(&chapters).expect("chapters are always valid JSON")
+}
+
+/// Write `<name>.chapters.vtt` and `<name>.chapters.json` next to `video`
+pub fn write_chapter_sidecars(video: &Path, chapters: &[Chapter]) -> io::Result<()> {
+    fs::write(
+        video.with_extension("chapters.vtt"),
+        chapters_webvtt(chapters),
+    )?;
+    fs::write(
+        video.with_extension("chapters.json"),
+        chapters_json(chapters),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::clip::Clip;
+
+    /// A 10 second project with chapters at 0, 4 (2 seconds long) and 6 seconds,
+    /// added out of order, and a marker and chapter that are ignored
+    fn project() -> Project {
+        let mut project = Project::new();
+        project.add_clip(Clip::new(0.0..10.0));
+
+        project.add_marker(Marker::chapter("Outro", TimeCode::from_seconds(6.0)));
+        project.add_marker(
+            Marker::chapter("Intro", TimeCode::from_seconds(0.0)).with_comment("Hello"),
+        );
+        project.add_marker(Marker::new("Fix color", TimeCode::from_seconds(1.0)));
+        project.add_marker(
+            Marker::chapter("Middle", TimeCode::from_seconds(4.0))
+                .with_duration(TimeCode::from_seconds(2.0)),
+        );
+        project.add_marker(Marker::chapter(
+            "After the end",
+            TimeCode::from_seconds(12.0),
+        ));
+
+        project
+    }
+
+    fn times(chapters: &[Chapter]) -> Vec<(&str, f64, f64)> {
+        chapters
+            .iter()
+            .map(|chapter| {
+                (
+                    chapter.title.as_str(),
+                    chapter.start.seconds(),
+                    chapter.end.seconds(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn chapters() {
+        let chapters = project().chapters();
+
+        // Sorted, ending at the next chapter, at their duration or with the project
+        assert_eq!(
+            times(&chapters),
+            [
+                ("Intro", 0.0, 4.0),
+                ("Middle", 4.0, 6.0),
+                ("Outro", 6.0, 10.0)
+            ]
+        );
+        assert_eq!(chapters[0].comment, "Hello");
+    }
+
+    #[test]
+    fn chapters_end_with_the_project() {
+        let mut project = project();
+        project.add_marker(
+            Marker::chapter("Long", TimeCode::from_seconds(8.0))
+                .with_duration(TimeCode::from_seconds(5.0)),
+        );
+
+        let chapters = project.chapters();
+        assert_eq!(times(&chapters)[3], ("Long", 8.0, 10.0));
+    }
+
+    #[test]
+    fn chapters_in_range() {
+        let chapters =
+            project().chapters_in(TimeCode::from_seconds(5.0), TimeCode::from_seconds(8.0));
+
+        assert_eq!(
+            times(&chapters),
+            [("Middle", 0.0, 1.0), ("Outro", 1.0, 3.0)]
+        );
+    }
+
+    #[test]
+    fn webvtt_timestamps() {
+        assert_eq!(
+            webvtt_timestamp(TimeCode::from_seconds(0.0)),
+            "00:00:00.000"
+        );
+        assert_eq!(
+            webvtt_timestamp(TimeCode::from_seconds(61.5)),
+            "00:01:01.500"
+        );
+        assert_eq!(
+            webvtt_timestamp(TimeCode::from_seconds(3600.0)),
+            "01:00:00.000"
+        );
+        assert_eq!(
+            webvtt_timestamp(TimeCode::from_seconds(45296.789)),
+            "12:34:56.789"
+        );
+        assert_eq!(
+            webvtt_timestamp(TimeCode::from_seconds(360000.0)),
+            "100:00:00.000"
+        );
+    }
+
+    #[test]
+    fn webvtt() {
+        let mut chapters = project().chapters();
+        chapters[1].title = "Part 1 --> Part 2\n\nThe end".to_string();
+
+        assert_eq!(
+            chapters_webvtt(&chapters),
+            "WEBVTT\n\
+             \n1\n00:00:00.000 --> 00:00:04.000\nIntro\n\
+             \n2\n00:00:04.000 --> 00:00:06.000\nPart 1 -> Part 2 The end\n\
+             \n3\n00:00:06.000 --> 00:00:10.000\nOutro\n"
+        );
+    }
+}
@@ -0,0 +1,75 @@
+use serde::{Deserialize, Serialize};
+use vide_animate::AnimatedProperty;
+use vide_common::layout::{Position, Size};
+
+use crate::clip::Clip;
+
+/// Which part of a matte clip decides where the matted clip is visible
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MatteMode {
+    Alpha,
+    InvertedAlpha,
+    Luma,
+    InvertedLuma,
+}
+
+/// Uses the rendered output of another clip to cut out a clip. The matte clip is
+/// positioned like a sibling of the clip it is attached to, and is never visible
+/// on its own.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrackMatte {
+    pub mode: MatteMode,
+    pub source: Box<Clip>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MaskShape {
+    Rectangle,
+    Ellipse,
+}
+
+/// How a mask is combined with the masks before it
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MaskMode {
+    #[default]
+    Add,
+    Subtract,
+    Intersect,
+}
+
+/// A vector shape that limits where a clip is visible. Masks live in the space
+/// of the clip they're attached to, so they follow its transform.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Mask {
+    pub shape: MaskShape,
+    pub mode: MaskMode,
+    pub inverted: bool,
+    /// Center of the shape
+    pub position: AnimatedProperty<Position>,
+    pub size: AnimatedProperty<Size>,
+    /// Corner radius in pixels, only used by [`MaskShape::Rectangle`]
+    pub roundness: AnimatedProperty<f64>,
+    /// Width of the soft edge in pixels
+    pub feather: AnimatedProperty<f64>,
+    /// Grows (or shrinks when negative) the shape by this many pixels
+    pub expansion: AnimatedProperty<f64>,
+}
+
+impl Mask {
+    pub fn new(
+        shape: MaskShape,
+        position: AnimatedProperty<Position>,
+        size: AnimatedProperty<Size>,
+    ) -> Self {
+        Self {
+            shape,
+            mode: MaskMode::Add,
+            inverted: false,
+            position,
+            size,
+            roundness: AnimatedProperty::default(),
+            feather: AnimatedProperty::default(),
+            expansion: AnimatedProperty::default(),
+        }
+    }
+}
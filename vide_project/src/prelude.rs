@@ -1,2 +1,8 @@
-pub use crate::clip::Clip;
+pub use crate::canvas::Canvas;
+pub use crate::clip::{Clip, ClipId, DurationInference};
+pub use crate::marker::{Marker, MarkerKind};
+pub use crate::matte::{Mask, MaskMode, MaskShape, MatteMode};
+pub use crate::time_remap::{Looping, TimeRemap};
+pub use crate::track::{ClipParent, Track, TrackId, TrackKind};
+pub use crate::transition::{Direction, Transition, TransitionKind};
 pub use crate::Project;
@@ -0,0 +1,302 @@
+use serde::{Deserialize, Serialize};
+use vide_animate::AnimatedProperty;
+use vide_common::{prelude::TimeCode, types::TimeUnit};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Looping {
+    #[default]
+    Off,
+    /// Play the content this many times, after which it continues past its end. `0`
+    /// plays it once.
+    Times(u32),
+    Forever,
+}
+
+/// Maps the local time of a clip to the time its children and video see.
+///
+/// The curve (or speed) is applied first, then looping, then reversing. Looping and
+/// reversing need to know how long the content is, see [`Clip::content_duration`](crate::clip::Clip::content_duration).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimeRemap {
+    /// Constant playback speed, `2.0` plays the content twice as fast
+    pub speed: f64,
+    pub reverse: bool,
+    /// Show the content at this time for the whole clip
+    pub freeze: Option<TimeCode>,
+    pub looping: Looping,
+    /// Content time in seconds for every local time, replaces `speed`. Use this
+    /// for speed ramps.
+    pub curve: Option<AnimatedProperty<f64>>,
+}
+
+impl TimeRemap {
+    pub fn new() -> Self {
+        Self {
+            speed: 1.0,
+            reverse: false,
+            freeze: None,
+            looping: Looping::Off,
+            curve: None,
+        }
+    }
+
+    pub fn with_speed(speed: f64) -> Self {
+        Self {
+            speed,
+            ..Self::new()
+        }
+    }
+
+    pub fn frozen_at(time_code: impl Into<TimeCode>) -> Self {
+        Self {
+            freeze: Some(time_code.into()),
+            ..Self::new()
+        }
+    }
+
+    pub fn with_curve(curve: AnimatedProperty<f64>) -> Self {
+        Self {
+            curve: Some(curve),
+            ..Self::new()
+        }
+    }
+
+    pub fn is_identity(&self) -> bool {
+        self.speed == 1.0
+            && !self.reverse
+            && self.freeze.is_none()
+            && self.looping == Looping::Off
+            && self.curve.is_none()
+    }
+
+    pub fn remap(&self, local: TimeCode, content_duration: Option<TimeCode>) -> TimeCode {
+        if let Some(freeze) = self.freeze {
+            return freeze;
+        }
+
+        let mut time_code = match self.curve.as_ref() {
+            Some(curve) => TimeUnit::Seconds(curve.evaluate(local)).into(),
+            None => TimeCode::new((local.value() as f64 * self.speed) as i64),
+        };
+
+        let Some(duration) = content_duration.filter(|d| d.value() > 0) else {
+            return time_code;
+        };
+
+        time_code = match self.looping {
+            Looping::Off => time_code,
+            Looping::Times(times) => {
+                // Playing 0 times is the same as playing once, like `Clip::natural_duration`
+                let times = times.max(1);
+                if time_code.value() < duration.value() * times as i64 {
+                    TimeCode::new(time_code.value().rem_euclid(duration.value()))
+                } else {
+                    TimeCode::new(time_code.value() - duration.value() * (times as i64 - 1))
+                }
+            }
+            Looping::Forever => TimeCode::new(time_code.value().rem_euclid(duration.value())),
+        };
+
+        if self.reverse {
+            // The end of a range is exclusive, stay one tick before it
+            time_code = TimeCode::new((duration.value() - 1 - time_code.value()).max(0));
+        }
+
+        time_code
+    }
+}
+
+impl Default for TimeRemap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identity() {
+        let remap = TimeRemap::new();
+
+        assert!(remap.is_identity());
+        assert_eq!(
+            remap.remap(
+                TimeCode::from_seconds(1.5),
+                Some(TimeCode::from_seconds(1.0))
+            ),
+            TimeCode::from_seconds(1.5)
+        );
+    }
+
+    #[test]
+    fn speed() {
+        let remap = TimeRemap::with_speed(2.0);
+
+        assert_eq!(
+            remap.remap(TimeCode::from_seconds(1.0), None),
+            TimeCode::from_seconds(2.0)
+        );
+        assert_eq!(
+            remap.remap(
+                TimeCode::from_seconds(1.0),
+                Some(TimeCode::from_seconds(1.0))
+            ),
+            TimeCode::from_seconds(2.0)
+        );
+        assert_eq!(
+            TimeRemap::with_speed(0.5).remap(TimeCode::from_seconds(3.0), None),
+            TimeCode::from_seconds(1.5)
+        );
+    }
+
+    #[test]
+    fn reverse() {
+        let remap = TimeRemap {
+            reverse: true,
+            ..TimeRemap::new()
+        };
+        let tick = TimeCode::new(1);
+
+        assert_eq!(
+            remap.remap(
+                TimeCode::from_seconds(0.0),
+                Some(TimeCode::from_seconds(3.0))
+            ),
+            TimeCode::from_seconds(3.0) - tick
+        );
+        assert_eq!(
+            remap.remap(
+                TimeCode::from_seconds(1.0),
+                Some(TimeCode::from_seconds(3.0))
+            ),
+            TimeCode::from_seconds(2.0) - tick
+        );
+        // Past the end the content stays on its first frame
+        assert_eq!(
+            remap.remap(
+                TimeCode::from_seconds(5.0),
+                Some(TimeCode::from_seconds(3.0))
+            ),
+            TimeCode::from_seconds(0.0)
+        );
+        // Without a duration there is nothing to reverse
+        assert_eq!(
+            remap.remap(TimeCode::from_seconds(1.0), None),
+            TimeCode::from_seconds(1.0)
+        );
+    }
+
+    #[test]
+    fn freeze() {
+        let remap = TimeRemap::frozen_at(TimeCode::from_seconds(0.5));
+
+        assert_eq!(
+            remap.remap(
+                TimeCode::from_seconds(0.0),
+                Some(TimeCode::from_seconds(3.0))
+            ),
+            TimeCode::from_seconds(0.5)
+        );
+        assert_eq!(
+            remap.remap(TimeCode::from_seconds(10.0), None),
+            TimeCode::from_seconds(0.5)
+        );
+    }
+
+    #[test]
+    fn looping() {
+        let times = TimeRemap {
+            looping: Looping::Times(2),
+            ..TimeRemap::new()
+        };
+
+        assert_eq!(
+            times.remap(
+                TimeCode::from_seconds(0.5),
+                Some(TimeCode::from_seconds(1.0))
+            ),
+            TimeCode::from_seconds(0.5)
+        );
+        assert_eq!(
+            times.remap(
+                TimeCode::from_seconds(1.5),
+                Some(TimeCode::from_seconds(1.0))
+            ),
+            TimeCode::from_seconds(0.5)
+        );
+        // After the last loop the content continues past its end
+        assert_eq!(
+            times.remap(
+                TimeCode::from_seconds(2.5),
+                Some(TimeCode::from_seconds(1.0))
+            ),
+            TimeCode::from_seconds(1.5)
+        );
+
+        let forever = TimeRemap {
+            looping: Looping::Forever,
+            ..TimeRemap::new()
+        };
+
+        assert_eq!(
+            forever.remap(
+                TimeCode::from_seconds(7.25),
+                Some(TimeCode::from_seconds(1.0))
+            ),
+            TimeCode::from_seconds(0.25)
+        );
+    }
+
+    #[test]
+    fn looping_zero_times_plays_once() {
+        let zero = TimeRemap {
+            looping: Looping::Times(0),
+            ..TimeRemap::new()
+        };
+        let once = TimeRemap {
+            looping: Looping::Times(1),
+            ..TimeRemap::new()
+        };
+
+        for time in [0.0, 0.5, 1.5, 3.0] {
+            assert_eq!(
+                zero.remap(
+                    TimeCode::from_seconds(time),
+                    Some(TimeCode::from_seconds(1.0))
+                ),
+                TimeCode::from_seconds(time)
+            );
+            assert_eq!(
+                zero.remap(
+                    TimeCode::from_seconds(time),
+                    Some(TimeCode::from_seconds(1.0))
+                ),
+                once.remap(
+                    TimeCode::from_seconds(time),
+                    Some(TimeCode::from_seconds(1.0))
+                )
+            );
+        }
+    }
+
+    #[test]
+    fn looping_with_speed_and_reverse() {
+        let remap = TimeRemap {
+            speed: 2.0,
+            reverse: true,
+            looping: Looping::Forever,
+            ..TimeRemap::new()
+        };
+
+        // 1.25s at double speed is 2.5s, 0.5s into the third loop, reversed
+        assert_eq!(
+            remap.remap(
+                TimeCode::from_seconds(1.25),
+                Some(TimeCode::from_seconds(1.0))
+            ),
+            TimeCode::from_seconds(0.5) - TimeCode::new(1)
+        );
+    }
+}
@@ -0,0 +1,182 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::{
+    clip::{Clip, ClipId},
+    transition::Transition,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+pub struct TrackId(u64);
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+impl TrackId {
+    fn next() -> Self {
+        Self(NEXT_ID.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+impl<'de> Deserialize<'de> for TrackId {
+    /// Loaded ids are kept, new ids are never handed out again
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let id = u64::deserialize(deserializer)?;
+        NEXT_ID.fetch_max(id + 1, Ordering::Relaxed);
+        Ok(Self(id))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum TrackKind {
+    Video,
+    Audio,
+}
+
+/// Where a clip is placed, either directly on a track or as a child of another clip
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ClipParent {
+    Track(TrackId),
+    Clip(ClipId),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Track {
+    id: TrackId,
+    name: String,
+    kind: TrackKind,
+    z_index: i32,
+    locked: bool,
+    solo: bool,
+    muted: bool,
+    enabled: bool,
+    guide: bool,
+    clips: Vec<Clip>,
+    transitions: Vec<Transition>,
+}
+
+impl Track {
+    pub fn new(name: impl Into<String>, kind: TrackKind) -> Self {
+        Self {
+            id: TrackId::next(),
+            name: name.into(),
+            kind,
+            z_index: 0,
+            locked: false,
+            solo: false,
+            muted: false,
+            enabled: true,
+            guide: false,
+            clips: Vec::new(),
+            transitions: Vec::new(),
+        }
+    }
+
+    pub fn video(name: impl Into<String>) -> Self {
+        Self::new(name, TrackKind::Video)
+    }
+
+    pub fn audio(name: impl Into<String>) -> Self {
+        Self::new(name, TrackKind::Audio)
+    }
+
+    pub fn id(&self) -> TrackId {
+        self.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn set_name(&mut self, name: impl Into<String>) {
+        self.name = name.into();
+    }
+
+    pub fn kind(&self) -> TrackKind {
+        self.kind
+    }
+
+    pub fn z_index(&self) -> i32 {
+        self.z_index
+    }
+
+    /// Tracks with a higher z-index are drawn on top, tracks with the same z-index are
+    /// drawn in the order they were added
+    pub fn set_z_index(&mut self, z_index: i32) {
+        self.z_index = z_index;
+    }
+
+    pub fn locked(&self) -> bool {
+        self.locked
+    }
+
+    /// Editing operations on [`Project`](crate::Project) refuse to touch locked tracks
+    pub fn set_locked(&mut self, locked: bool) {
+        self.locked = locked;
+    }
+
+    pub fn solo(&self) -> bool {
+        self.solo
+    }
+
+    /// When any track is solo, only solo tracks are rendered
+    pub fn set_solo(&mut self, solo: bool) {
+        self.solo = solo;
+    }
+
+    pub fn muted(&self) -> bool {
+        self.muted
+    }
+
+    /// Metadata only, there is no audio rendering and the video renderer ignores it.
+    /// Use [`Track::set_enabled`] to hide a video track.
+    pub fn set_muted(&mut self, muted: bool) {
+        self.muted = muted;
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    pub fn guide(&self) -> bool {
+        self.guide
+    }
+
+    /// Guide tracks are for reference while editing and never end up in a render
+    pub fn set_guide(&mut self, guide: bool) {
+        self.guide = guide;
+    }
+
+    pub fn clips(&self) -> &[Clip] {
+        &self.clips
+    }
+
+    pub fn clips_mut(&mut self) -> &mut [Clip] {
+        &mut self.clips
+    }
+
+    pub(crate) fn clips_vec_mut(&mut self) -> &mut Vec<Clip> {
+        &mut self.clips
+    }
+
+    pub fn add_clip(&mut self, clip: Clip) {
+        self.clips.push(clip);
+    }
+
+    pub fn transitions(&self) -> &[Transition] {
+        &self.transitions
+    }
+
+    pub fn transitions_mut(&mut self) -> &mut Vec<Transition> {
+        &mut self.transitions
+    }
+
+    /// Add a transition between two clips on this track
+    pub fn add_transition(&mut self, transition: Transition) {
+        self.transitions.push(transition);
+    }
+}
@@ -0,0 +1,73 @@
+use serde::{Deserialize, Serialize};
+use vide_common::prelude::{Color, TimeCode};
+
+use crate::clip::ClipId;
+
+/// The direction something moves in during a transition
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum Direction {
+    #[default]
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum TransitionKind {
+    Crossfade,
+    /// Fade to `color`, then fade in the next clip
+    DipToColor(Color),
+    /// Reveal the next clip behind an edge moving in `direction`, `softness` is the
+    /// width of the edge relative to the frame
+    Wipe {
+        direction: Direction,
+        softness: f64,
+    },
+    /// The next clip slides in over the current one
+    Slide(Direction),
+    /// The next clip pushes the current one out of frame
+    Push(Direction),
+    /// The current clip zooms in while the next one fades in
+    Zoom,
+    /// A WGSL snippet defining `fn transition(uv: vec2<f32>, progress: f32) -> vec4<f32>`,
+    /// which can use `sample_from(uv)` and `sample_to(uv)` to read both clips
+    Custom(String),
+}
+
+/// A transition from one clip to the next one on the same track, centered around the
+/// start of `to`. The clips play past their in and out points during the transition,
+/// so they need enough content (handles) to cover half of `duration`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Transition {
+    pub kind: TransitionKind,
+    pub from: ClipId,
+    pub to: ClipId,
+    pub duration: TimeCode,
+}
+
+impl Transition {
+    pub fn new(kind: TransitionKind, from: ClipId, to: ClipId, duration: TimeCode) -> Self {
+        Self {
+            kind,
+            from,
+            to,
+            duration,
+        }
+    }
+
+    pub fn crossfade(from: ClipId, to: ClipId, duration: TimeCode) -> Self {
+        Self::new(TransitionKind::Crossfade, from, to, duration)
+    }
+
+    /// How far along this transition is at `time_code`, if it's active. `cut` is the start
+    /// of the `to` clip.
+    pub fn progress(&self, cut: TimeCode, time_code: TimeCode) -> Option<f64> {
+        let start = cut - TimeCode::new(self.duration.value() / 2);
+        let end = start + self.duration;
+
+        (start..end)
+            .contains(&time_code)
+            .then(|| (time_code - start).value() as f64 / self.duration.value() as f64)
+    }
+}
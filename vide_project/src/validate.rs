@@ -0,0 +1,554 @@
+//! Checks for mistakes that would otherwise render silently or strangely, see
+//! [`Project::validate`]
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+};
+
+use vide_animate::{interpolate::Interpolate, AnimatedProperty};
+use vide_common::prelude::TimeCode;
+
+use crate::{
+    clip::{Clip, ClipId, DurationInference},
+    track::{Track, TrackId},
+    Project,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// The project renders, but probably not as intended
+    Warning,
+    /// Part of the project is ignored when rendering
+    Error,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DiagnosticKind {
+    /// The project has no clip with an end, so its duration falls back to 5 seconds
+    DefaultDuration,
+    /// A top-level clip has no end and doesn't count towards the project duration
+    UnboundedClip,
+    /// A clip ends before (or when) it starts
+    EmptyRange,
+    /// A child clip ends after its parent and is cut off
+    ChildPastParent,
+    /// A property has keyframes after the clip ends, they are never reached
+    KeyframePastEnd,
+    /// A transition refers to a clip that is not on its track
+    MissingTransitionClip,
+    /// A clip's transform parent doesn't exist
+    MissingTransformParent,
+    /// Clips are each other's transform parent
+    TransformParentCycle,
+}
+
+/// Where a diagnostic was found, the track and the clips from the top-level clip down
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClipPath {
+    pub track: TrackId,
+    pub track_name: String,
+    pub clips: Vec<(ClipId, String)>,
+}
+
+impl ClipPath {
+    /// The clip the diagnostic is about
+    pub fn clip(&self) -> Option<ClipId> {
+        self.clips.last().map(|(id, _)| *id)
+    }
+
+    fn child(&self, clip: &Clip) -> Self {
+        let mut path = self.clone();
+        path.clips.push((clip.id(), clip.name().to_string()));
+        path
+    }
+}
+
+impl Display for ClipPath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.track_name)?;
+
+        for (id, name) in &self.clips {
+            if name.is_empty() {
+                write!(f, " / {id}")?;
+            } else {
+                write!(f, " / {name}")?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub kind: DiagnosticKind,
+    /// `None` for problems with the project as a whole
+    pub path: Option<ClipPath>,
+    pub message: String,
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let severity = match self.severity {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+
+        match &self.path {
+            Some(path) => write!(f, "{severity}: {path}: {}", self.message),
+            None => write!(f, "{severity}: {}", self.message),
+        }
+    }
+}
+
+fn seconds(time_code: TimeCode) -> String {
+    format!("{:.2}s", time_code.seconds())
+}
+
+/// Whether `clip` gets an end from [`Project::infer_durations`]
+fn inferred(clip: &Clip) -> bool {
+    clip.duration_inference() == DurationInference::Content && clip.natural_duration().is_some()
+}
+
+/// Add `name` to `late` if `property` has keyframes after `duration`
+fn check_keyframes<T: Interpolate + std::fmt::Debug + Clone>(
+    late: &mut Vec<String>,
+    name: impl Into<String>,
+    property: &AnimatedProperty<T>,
+    duration: TimeCode,
+) {
+    if let Some(time) = property
+        .last_keyframe_time()
+        .filter(|time| *time > duration)
+    {
+        late.push(format!("{} ({})", name.into(), seconds(time)));
+    }
+}
+
+struct Validator {
+    diagnostics: Vec<Diagnostic>,
+    transform_parents: HashMap<ClipId, (Option<ClipId>, ClipPath)>,
+}
+
+impl Validator {
+    fn push(&mut self, severity: Severity, kind: DiagnosticKind, path: &ClipPath, message: String) {
+        self.diagnostics.push(Diagnostic {
+            severity,
+            kind,
+            path: Some(path.clone()),
+            message,
+        });
+    }
+
+    fn track(&mut self, track: &Track) {
+        let path = ClipPath {
+            track: track.id(),
+            track_name: track.name().to_string(),
+            clips: Vec::new(),
+        };
+
+        for clip in track.clips() {
+            let path = path.child(clip);
+
+            // Inferred clips get an end when rendering
+            if clip.range().end().is_none() && !inferred(clip) {
+                let hint = if clip.natural_duration().is_some() {
+                    ", turn on its duration inference to end it with its video"
+                } else {
+                    ""
+                };
+
+                self.push(
+                    Severity::Warning,
+                    DiagnosticKind::UnboundedClip,
+                    &path,
+                    format!("clip has no end and doesn't extend the project{hint}"),
+                );
+            }
+
+            self.clip(clip, track.clips(), &path);
+        }
+
+        for transition in track.transitions() {
+            for id in [transition.from, transition.to] {
+                if !track.clips().iter().any(|clip| clip.id() == id) {
+                    self.push(
+                        Severity::Error,
+                        DiagnosticKind::MissingTransitionClip,
+                        &path,
+                        format!("transition refers to clip {id}, which is not on this track"),
+                    );
+                }
+            }
+        }
+    }
+
+    /// Check `clip` and its children, `siblings` includes `clip` itself
+    fn clip(&mut self, clip: &Clip, siblings: &[Clip], path: &ClipPath) {
+        self.transform_parents
+            .insert(clip.id(), (clip.transform_parent(), path.clone()));
+
+        let range = clip.range();
+        if let (Some(start), Some(end)) = (range.start(), range.end()) {
+            if end <= start {
+                self.push(
+                    Severity::Error,
+                    DiagnosticKind::EmptyRange,
+                    path,
+                    format!(
+                        "clip ends at {} but starts at {}",
+                        seconds(end),
+                        seconds(start)
+                    ),
+                );
+            }
+        }
+
+        // Same as `UnboundedTimecodeRange::make_absolute`
+        let duration = range
+            .end()
+            .map(|end| end - range.start().unwrap_or_default());
+
+        if let Some(duration) = duration {
+            // The second half of a split picks up the animation where this clip ends
+            let continued = siblings.iter().any(|next| {
+                next.range().start() == range.end()
+                    && next.animation_offset() == clip.animation_offset() + duration
+            });
+
+            if !continued {
+                self.keyframes(clip, duration, path);
+            }
+
+            // Remapped children can play past the end of the clip
+            let remapped = !clip.time_remap().is_identity() || clip.content_offset().value() != 0;
+
+            for child in clip.children().iter().filter(|_| !remapped) {
+                if let Some(end) = child.range().end().filter(|end| *end > duration) {
+                    self.push(
+                        Severity::Warning,
+                        DiagnosticKind::ChildPastParent,
+                        &path.child(child),
+                        format!(
+                            "clip ends at {} but its parent is only {} long, it is cut off",
+                            seconds(end),
+                            seconds(duration)
+                        ),
+                    );
+                }
+            }
+        }
+
+        for child in clip.children() {
+            self.clip(child, clip.children(), &path.child(child));
+        }
+
+        if let Some(track_matte) = clip.track_matte() {
+            self.clip(&track_matte.source, &[], &path.child(&track_matte.source));
+        }
+    }
+
+    fn keyframes(&mut self, clip: &Clip, duration: TimeCode, path: &ClipPath) {
+        let mut late = Vec::new();
+
+        // Split and trimmed clips start partway into their animation and content
+        let animation_end = duration + clip.animation_offset();
+        let content_end = duration + clip.content_offset();
+
+        check_keyframes(&mut late, "position", clip.position(), animation_end);
+        check_keyframes(&mut late, "rotation", clip.rotation(), animation_end);
+        check_keyframes(&mut late, "scale", clip.scale(), animation_end);
+        check_keyframes(&mut late, "anchor", clip.anchor(), animation_end);
+        check_keyframes(&mut late, "opacity", clip.opacity(), animation_end);
+
+        for (index, mask) in clip.masks().iter().enumerate() {
+            check_keyframes(
+                &mut late,
+                format!("mask {index} position"),
+                &mask.position,
+                animation_end,
+            );
+            check_keyframes(
+                &mut late,
+                format!("mask {index} size"),
+                &mask.size,
+                animation_end,
+            );
+            check_keyframes(
+                &mut late,
+                format!("mask {index} roundness"),
+                &mask.roundness,
+                animation_end,
+            );
+            check_keyframes(
+                &mut late,
+                format!("mask {index} feather"),
+                &mask.feather,
+                animation_end,
+            );
+            check_keyframes(
+                &mut late,
+                format!("mask {index} expansion"),
+                &mask.expansion,
+                animation_end,
+            );
+        }
+
+        if let Some(curve) = &clip.time_remap().curve {
+            check_keyframes(&mut late, "time remap curve", curve, content_end);
+        }
+
+        if !late.is_empty() {
+            self.push(
+                Severity::Warning,
+                DiagnosticKind::KeyframePastEnd,
+                path,
+                format!(
+                    "clip is {} long but has keyframes after its end: {}",
+                    seconds(duration),
+                    late.join(", ")
+                ),
+            );
+        }
+    }
+
+    fn transform_parents(&mut self) {
+        let mut reported = HashSet::new();
+        let mut clips = self.transform_parents.keys().copied().collect::<Vec<_>>();
+        clips.sort();
+
+        for id in clips {
+            let (Some(parent), path) = self.transform_parents[&id].clone() else {
+                continue;
+            };
+
+            if !self.transform_parents.contains_key(&parent) {
+                self.push(
+                    Severity::Error,
+                    DiagnosticKind::MissingTransformParent,
+                    &path,
+                    format!("transform parent {parent} doesn't exist, it is ignored"),
+                );
+                continue;
+            }
+
+            let mut chain = vec![id];
+            let mut current = Some(parent);
+            while let Some(next) = current {
+                if next == id {
+                    if !reported.contains(&id) {
+                        self.push(
+                            Severity::Error,
+                            DiagnosticKind::TransformParentCycle,
+                            &path,
+                            "clip is its own transform parent (through other clips), \
+                             the cycle is ignored"
+                                .to_string(),
+                        );
+                    }
+                    reported.extend(chain);
+                    break;
+                }
+
+                // A cycle further up, reported from one of its clips
+                if chain.contains(&next) {
+                    break;
+                }
+
+                chain.push(next);
+                current = self
+                    .transform_parents
+                    .get(&next)
+                    .and_then(|(parent, _)| *parent);
+            }
+        }
+    }
+}
+
+impl Project {
+    /// Check the project for mistakes that would render silently or strangely.
+    /// Rendering runs this first, how strict it is can be configured with
+    /// `RenderConfiguration::strictness`.
+    pub fn validate(&self) -> Vec<Diagnostic> {
+        let mut validator = Validator {
+            diagnostics: Vec::new(),
+            transform_parents: HashMap::new(),
+        };
+
+        for track in self.tracks() {
+            validator.track(track);
+        }
+
+        validator.transform_parents();
+
+        if self.explicit_duration().is_none()
+            && self
+                .rendered_tracks()
+                .into_iter()
+                .flat_map(|track| track.clips())
+                .all(|clip| clip.range().end().is_none() && !inferred(clip))
+        {
+            validator.diagnostics.push(Diagnostic {
+                severity: Severity::Warning,
+                kind: DiagnosticKind::DefaultDuration,
+                path: None,
+                message: format!(
+                    "no clip has an end, the project is {} long",
+                    seconds(self.duration())
+                ),
+            });
+        }
+
+        validator.diagnostics
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use vide_animate::KeyframeTiming;
+    use vide_common::types::TimeUnit;
+
+    use super::*;
+    use crate::transition::{Transition, TransitionKind};
+
+    fn kinds(project: &Project) -> Vec<DiagnosticKind> {
+        project
+            .validate()
+            .into_iter()
+            .map(|diagnostic| diagnostic.kind)
+            .collect()
+    }
+
+    #[test]
+    fn valid_project() {
+        let mut project = Project::new();
+        let mut clip = Clip::new(0.0..2.0);
+        clip.add_clip(Clip::new(0.5..1.5));
+        project.add_clip(clip);
+
+        assert!(project.validate().is_empty());
+    }
+
+    #[test]
+    fn default_duration() {
+        let mut project = Project::new();
+        assert_eq!(kinds(&project), [DiagnosticKind::DefaultDuration]);
+
+        // Clips on tracks that aren't rendered don't give the project a duration
+        let mut guide = Track::video("Guide");
+        guide.set_guide(true);
+        guide.add_clip(Clip::new(0.0..2.0));
+        project.add_track(guide);
+        assert_eq!(kinds(&project), [DiagnosticKind::DefaultDuration]);
+
+        project.set_duration(Some(TimeCode::from_seconds(2.0)));
+        assert!(project.validate().is_empty());
+    }
+
+    #[test]
+    fn unbounded_clip() {
+        let mut project = Project::new();
+        project.add_clip(Clip::new(0.0..2.0));
+        project.add_clip(Clip::new(1.0..));
+
+        assert_eq!(kinds(&project), [DiagnosticKind::UnboundedClip]);
+    }
+
+    #[test]
+    fn empty_range() {
+        let mut project = Project::new();
+        project.add_clip(Clip::new(2.0..1.0));
+
+        let diagnostics = project.validate();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].kind, DiagnosticKind::EmptyRange);
+        assert_eq!(diagnostics[0].severity, Severity::Error);
+    }
+
+    #[test]
+    fn child_past_parent() {
+        let mut project = Project::new();
+        let mut parent = Clip::new(0.0..2.0);
+        let child = Clip::new(1.0..3.0);
+        let child_id = child.id();
+        parent.add_clip(child);
+        project.add_clip(parent);
+
+        let diagnostics = project.validate();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].kind, DiagnosticKind::ChildPastParent);
+        assert_eq!(
+            diagnostics[0].path.as_ref().and_then(ClipPath::clip),
+            Some(child_id)
+        );
+    }
+
+    #[test]
+    fn keyframe_past_end() {
+        let mut project = Project::new();
+        let mut clip = Clip::new(0.0..2.0);
+        clip.set_opacity(
+            AnimatedProperty::builder()
+                .keyframe(KeyframeTiming::Abs(TimeUnit::Seconds(0.0)), 0.0)
+                .keyframe(KeyframeTiming::Abs(TimeUnit::Seconds(3.0)), 1.0)
+                .build(),
+        );
+        project.add_clip(clip);
+
+        let diagnostics = project.validate();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].kind, DiagnosticKind::KeyframePastEnd);
+        assert!(diagnostics[0].message.contains("opacity (3.00s)"));
+    }
+
+    #[test]
+    fn missing_transition_clip() {
+        let mut project = Project::new();
+        let clip = Clip::new(0.0..2.0);
+        let other = Clip::new(2.0..4.0);
+        let transition = Transition::new(
+            TransitionKind::Crossfade,
+            clip.id(),
+            other.id(),
+            TimeCode::from_seconds(0.5),
+        );
+        project.add_clip(clip);
+        project.default_track_mut().add_transition(transition);
+
+        let diagnostics = project.validate();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].kind, DiagnosticKind::MissingTransitionClip);
+        assert_eq!(diagnostics[0].severity, Severity::Error);
+    }
+
+    #[test]
+    fn missing_transform_parent() {
+        let mut project = Project::new();
+        let removed = Clip::new(0.0..1.0);
+        let mut clip = Clip::new(0.0..2.0);
+        clip.set_transform_parent(Some(removed.id()));
+        project.add_clip(clip);
+
+        assert_eq!(kinds(&project), [DiagnosticKind::MissingTransformParent]);
+    }
+
+    #[test]
+    fn transform_parent_cycle() {
+        let mut project = Project::new();
+        let mut a = Clip::new(0.0..2.0);
+        let mut b = Clip::new(0.0..2.0);
+        let mut c = Clip::new(0.0..2.0);
+        a.set_transform_parent(Some(b.id()));
+        b.set_transform_parent(Some(a.id()));
+        // Parented to the cycle, but not part of it
+        c.set_transform_parent(Some(a.id()));
+        project.add_clip(a);
+        project.add_clip(b);
+        project.add_clip(c);
+
+        // Reported once for the whole cycle
+        assert_eq!(kinds(&project), [DiagnosticKind::TransformParentCycle]);
+    }
+}
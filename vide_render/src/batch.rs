@@ -0,0 +1,69 @@
+use pollster::FutureExt;
+use vide_common::config::RenderConfiguration;
+use vide_project::Project;
+
+use crate::{init_wgpu, interface::OutputHandler, render_with};
+
+type ProjectOverride = Box<dyn FnOnce(&mut Project)>;
+
+/// One output format of a batch render, see [`render_batch`]
+pub struct RenderVariant {
+    config: RenderConfiguration,
+    output: Box<dyn OutputHandler>,
+    overrides: Vec<ProjectOverride>,
+}
+
+impl RenderVariant {
+    pub fn new(config: RenderConfiguration, output: impl OutputHandler + 'static) -> Self {
+        Self {
+            config,
+            output: Box::new(output),
+            overrides: Vec::new(),
+        }
+    }
+
+    /// Modify the project for this variant only, for example to reposition or
+    /// disable a clip. Overrides are applied in the order they were added.
+    pub fn with_override(mut self, apply: impl FnOnce(&mut Project) + 'static) -> Self {
+        self.overrides.push(Box::new(apply));
+        self
+    }
+
+    pub fn config(&self) -> &RenderConfiguration {
+        &self.config
+    }
+}
+
+/// Render the same project to multiple outputs (e.g. 16:9, 9:16 and 1:1), sharing
+/// a single device. Every variant renders its own copy of `project` with its
+/// overrides applied.
+pub fn render_batch(project: Project, variants: impl IntoIterator<Item = RenderVariant>) {
+    let _ = env_logger::try_init();
+
+    log::info!("Initializing wgpu");
+
+    let mut wgpu = init_wgpu().block_on();
+
+    for (index, variant) in variants.into_iter().enumerate() {
+        let RenderVariant {
+            config,
+            mut output,
+            overrides,
+        } = variant;
+
+        log::info!(
+            "Rendering variant #{index} ({}x{} @ {} fps)",
+            config.resolution.0,
+            config.resolution.1,
+            config.frames_per_second
+        );
+
+        let mut project = project.clone();
+
+        for apply in overrides {
+            apply(&mut project);
+        }
+
+        render_with(&mut wgpu, project, config, output.as_mut());
+    }
+}
@@ -1,6 +1,20 @@
-use vide_common::render::Wgpu;
+use vide_common::{blend_mode::BlendMode, render::Wgpu};
+use wgpu::util::DeviceExt;
 
 const BLEND_VERTEX_SOURCE: &str = include_str!("./shaders/blend/vertex.wgsl");
+const BLEND_COMMON_SOURCE: &str = include_str!("./shaders/blend/common.wgsl");
+
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy)]
+struct BlendUniform {
+    opacity: f32,
+    /// `a` has premultiplied alpha
+    premultiplied: u32,
+    _padding: [u32; 2],
+}
+
+unsafe impl bytemuck::Pod for BlendUniform {}
+unsafe impl bytemuck::Zeroable for BlendUniform {}
 
 #[derive(Debug)]
 pub struct Blend {
@@ -61,6 +75,16 @@ impl Blend {
                             ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
                             count: None,
                         },
+                        wgpu::BindGroupLayoutEntry {
+                            binding: 3,
+                            visibility: wgpu::ShaderStages::FRAGMENT,
+                            ty: wgpu::BindingType::Buffer {
+                                ty: wgpu::BufferBindingType::Uniform,
+                                has_dynamic_offset: false,
+                                min_binding_size: None,
+                            },
+                            count: None,
+                        },
                     ],
                 });
 
@@ -137,6 +161,7 @@ impl Blend {
         }
     }
 
+    /// Blend `a` over `b` into `target`, `opacity` is applied to `a`
     pub fn blend(
         &self,
         wgpu: &Wgpu,
@@ -144,7 +169,64 @@ impl Blend {
         a: &wgpu::TextureView,
         b: &wgpu::TextureView,
         target: &wgpu::TextureView,
+        opacity: f32,
+    ) {
+        self.blend_inner(wgpu, encoder, a, b, target, opacity, false, None);
+    }
+
+    /// Like [`Blend::blend`], for an `a` with premultiplied alpha
+    pub fn blend_premultiplied(
+        &self,
+        wgpu: &Wgpu,
+        encoder: &mut wgpu::CommandEncoder,
+        a: &wgpu::TextureView,
+        b: &wgpu::TextureView,
+        target: &wgpu::TextureView,
+        opacity: f32,
     ) {
+        self.blend_inner(wgpu, encoder, a, b, target, opacity, true, None);
+    }
+
+    /// Like [`Blend::blend`], but only inside `region` (`x, y, width, height` from the
+    /// top left) of `target`, the rest of `target` is left as it is
+    #[allow(clippy::too_many_arguments)]
+    pub fn blend_region(
+        &self,
+        wgpu: &Wgpu,
+        encoder: &mut wgpu::CommandEncoder,
+        a: &wgpu::TextureView,
+        b: &wgpu::TextureView,
+        target: &wgpu::TextureView,
+        opacity: f32,
+        region: [u32; 4],
+    ) {
+        self.blend_inner(wgpu, encoder, a, b, target, opacity, false, Some(region));
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn blend_inner(
+        &self,
+        wgpu: &Wgpu,
+        encoder: &mut wgpu::CommandEncoder,
+        a: &wgpu::TextureView,
+        b: &wgpu::TextureView,
+        target: &wgpu::TextureView,
+        opacity: f32,
+        premultiplied: bool,
+        region: Option<[u32; 4]>,
+    ) {
+        let uniform_buffer = wgpu
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Blend Uniform Buffer"),
+                contents: bytemuck::cast_slice(&[BlendUniform {
+                    opacity,
+                    premultiplied: premultiplied as u32,
+                    ..Default::default()
+                }]),
+                usage: wgpu::BufferUsages::UNIFORM,
+            });
+
         let bind_group = wgpu.device.create_bind_group(&wgpu::BindGroupDescriptor {
             label: Some("Blend Bind Group"),
             layout: &self.bind_group_layout,
@@ -161,6 +243,10 @@ impl Blend {
                     binding: 2,
                     resource: wgpu::BindingResource::Sampler(&self.sampler),
                 },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: uniform_buffer.as_entire_binding(),
+                },
             ],
         });
 
@@ -170,7 +256,10 @@ impl Blend {
                 view: target,
                 resolve_target: None,
                 ops: wgpu::Operations {
-                    load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                    load: match region {
+                        Some(_) => wgpu::LoadOp::Load,
+                        None => wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                    },
                     store: wgpu::StoreOp::Store,
                 },
             })],
@@ -179,6 +268,10 @@ impl Blend {
             occlusion_query_set: None,
         });
 
+        if let Some([x, y, width, height]) = region {
+            pass.set_scissor_rect(x, y, width, height);
+        }
+
         pass.set_pipeline(&self.pipeline);
         pass.set_bind_group(0, &bind_group, &[]);
         pass.draw(0..3, 0..1);
@@ -188,19 +281,82 @@ impl Blend {
 #[derive(Debug)]
 pub struct BlendModes {
     pub normal: Blend,
+    pub darken: Blend,
+    pub multiply: Blend,
+    pub color_burn: Blend,
+    pub lighten: Blend,
+    pub screen: Blend,
+    pub color_dodge: Blend,
+    pub add: Blend,
+    pub overlay: Blend,
+    pub soft_light: Blend,
+    pub hard_light: Blend,
+    pub difference: Blend,
+    pub exclusion: Blend,
+    pub subtract: Blend,
+    pub hue: Blend,
+    pub saturation: Blend,
+    pub color: Blend,
+    pub luminosity: Blend,
 }
 
 impl BlendModes {
     pub fn load(wgpu: &Wgpu, target: wgpu::TextureFormat) -> Self {
         log::info!("Loading blend modes for {target:?}");
 
-        let normal = Blend::new(
-            wgpu,
-            target,
-            BLEND_VERTEX_SOURCE,
-            include_str!("./shaders/blend/normal.wgsl"),
-        );
+        // Every blend mode only provides a `blend_color` function, the bindings
+        // and the actual compositing are shared
+        let load = |source: &str| {
+            Blend::new(
+                wgpu,
+                target,
+                BLEND_VERTEX_SOURCE,
+                &format!("{BLEND_COMMON_SOURCE}\n{source}"),
+            )
+        };
 
-        Self { normal }
+        Self {
+            normal: load(include_str!("./shaders/blend/normal.wgsl")),
+            darken: load(include_str!("./shaders/blend/darken.wgsl")),
+            multiply: load(include_str!("./shaders/blend/multiply.wgsl")),
+            color_burn: load(include_str!("./shaders/blend/color_burn.wgsl")),
+            lighten: load(include_str!("./shaders/blend/lighten.wgsl")),
+            screen: load(include_str!("./shaders/blend/screen.wgsl")),
+            color_dodge: load(include_str!("./shaders/blend/color_dodge.wgsl")),
+            add: load(include_str!("./shaders/blend/add.wgsl")),
+            overlay: load(include_str!("./shaders/blend/overlay.wgsl")),
+            soft_light: load(include_str!("./shaders/blend/soft_light.wgsl")),
+            hard_light: load(include_str!("./shaders/blend/hard_light.wgsl")),
+            difference: load(include_str!("./shaders/blend/difference.wgsl")),
+            exclusion: load(include_str!("./shaders/blend/exclusion.wgsl")),
+            subtract: load(include_str!("./shaders/blend/subtract.wgsl")),
+            hue: load(include_str!("./shaders/blend/hue.wgsl")),
+            saturation: load(include_str!("./shaders/blend/saturation.wgsl")),
+            color: load(include_str!("./shaders/blend/color.wgsl")),
+            luminosity: load(include_str!("./shaders/blend/luminosity.wgsl")),
+        }
+    }
+
+    pub fn get(&self, mode: BlendMode) -> &Blend {
+        match mode {
+            BlendMode::Normal => &self.normal,
+            BlendMode::Darken => &self.darken,
+            BlendMode::Multiply => &self.multiply,
+            BlendMode::ColorBurn => &self.color_burn,
+            BlendMode::Lighten => &self.lighten,
+            BlendMode::Screen => &self.screen,
+            BlendMode::ColorDodge => &self.color_dodge,
+            BlendMode::Add => &self.add,
+            BlendMode::Overlay => &self.overlay,
+            BlendMode::SoftLight => &self.soft_light,
+            BlendMode::HardLight => &self.hard_light,
+            BlendMode::Difference => &self.difference,
+            BlendMode::Exclusion => &self.exclusion,
+            BlendMode::Subtract => &self.subtract,
+            BlendMode::Hue => &self.hue,
+            BlendMode::Saturation => &self.saturation,
+            BlendMode::Color => &self.color,
+            BlendMode::Luminosity => &self.luminosity,
+        }
     }
 }
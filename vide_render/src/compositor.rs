@@ -0,0 +1,924 @@
+use std::collections::{HashMap, HashSet};
+
+use euler::Mat4;
+use vide_common::{
+    blend_mode::BlendMode, config::RenderConfiguration, effect::Effect, prelude::TimeCode,
+    render::Wgpu, standards::FRAGMENT_COLOR_TARGET, time_code::UnboundedTimecodeRange,
+    types::Resolution, FrameInfo,
+};
+use vide_project::{
+    clip::{Clip, ClipId},
+    matte::{Mask, MaskMode, MatteMode},
+    track::Track,
+    transition::TransitionKind,
+    Project,
+};
+
+use crate::{
+    blend::BlendModes,
+    matte::Mattes,
+    parenting::resolve_parented_transforms,
+    texture_factory::{FactoryTexture, TextureFactory},
+    transition::Transitions,
+};
+
+/// Composites the tracks of a project into a single texture, one frame at a time
+#[derive(Debug)]
+pub struct Compositor {
+    texture_factory: TextureFactory,
+    blend_modes: BlendModes,
+    mattes: Mattes,
+    transitions: Transitions,
+    /// Clips with a broken transform parent that were already reported
+    warned_parents: HashSet<ClipId>,
+}
+
+impl Compositor {
+    pub fn new(wgpu: &Wgpu, resolution: Resolution) -> Self {
+        let texture_factory = TextureFactory::new(
+            wgpu::TextureDescriptor {
+                label: Some("Compositor texture"),
+                dimension: wgpu::TextureDimension::D2,
+                format: FRAGMENT_COLOR_TARGET,
+                mip_level_count: 1,
+                sample_count: 1,
+                size: wgpu::Extent3d {
+                    width: resolution.0 as u32,
+                    height: resolution.1 as u32,
+                    depth_or_array_layers: 1,
+                },
+                usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                    | wgpu::TextureUsages::TEXTURE_BINDING,
+                view_formats: &[],
+            },
+            wgpu::TextureViewDescriptor::default(),
+        );
+
+        Self {
+            texture_factory,
+            blend_modes: BlendModes::load(wgpu, FRAGMENT_COLOR_TARGET),
+            mattes: Mattes::load(wgpu, FRAGMENT_COLOR_TARGET),
+            transitions: Transitions::load(wgpu, FRAGMENT_COLOR_TARGET),
+            warned_parents: HashSet::new(),
+        }
+    }
+
+    pub fn texture_factory(&self) -> &TextureFactory {
+        &self.texture_factory
+    }
+
+    /// Initialize every clip of `project` that ends up in a render, returns the number
+    /// of initialized clips
+    pub fn init_project(wgpu: &Wgpu, project: &mut Project, config: &RenderConfiguration) -> u32 {
+        let mut counter = 0u32;
+
+        for clip in project
+            .rendered_tracks_mut()
+            .into_iter()
+            .flat_map(|track| track.clips_mut())
+        {
+            init_clip(clip, wgpu, config, &mut counter);
+        }
+
+        counter
+    }
+
+    /// Compile the custom transitions of `project` up front, so broken shaders are
+    /// reported before rendering starts. Returns how many failed to compile.
+    pub fn prepare_transitions(&mut self, wgpu: &Wgpu, project: &Project) -> u32 {
+        let mut failed = 0;
+
+        for transition in project
+            .rendered_tracks()
+            .into_iter()
+            .flat_map(|track| track.transitions())
+        {
+            if let TransitionKind::Custom(source) = &transition.kind {
+                if !self.transitions.compile(wgpu, source) {
+                    failed += 1;
+                }
+            }
+        }
+
+        failed
+    }
+
+    /// Composite a frame of `project`, with `root_transform` applied to all of its
+    /// clips. Give the returned texture back using [`Compositor::return_texture`].
+    pub fn render_frame(
+        &mut self,
+        wgpu: &Wgpu,
+        encoder: &mut wgpu::CommandEncoder,
+        project: &mut Project,
+        frame_info: FrameInfo,
+        root_transform: Mat4,
+    ) -> FactoryTexture {
+        let project_range =
+            UnboundedTimecodeRange::new(Some(TimeCode::new(0)), Some(project.duration()));
+
+        let mut canvas_texture = self.texture_factory.borrow_texture(wgpu);
+        let mut blended_texture = self.texture_factory.borrow_texture(wgpu);
+
+        blended_texture.clear(encoder);
+
+        let parented_transforms = match project.clips().any(has_transform_parent) {
+            true => resolve_parented_transforms(
+                project,
+                frame_info,
+                project_range,
+                root_transform,
+                &mut self.warned_parents,
+            ),
+            false => HashMap::new(),
+        };
+
+        let mut ctx = RenderContext {
+            wgpu,
+            encoder,
+            texture_factory: &mut self.texture_factory,
+            blend_modes: &self.blend_modes,
+            mattes: &self.mattes,
+            transitions: &mut self.transitions,
+            parented_transforms: &parented_transforms,
+        };
+
+        for track in project.rendered_tracks_mut() {
+            render_track(
+                track,
+                &mut ctx,
+                frame_info,
+                project_range,
+                root_transform,
+                &mut canvas_texture,
+                &mut blended_texture,
+            );
+        }
+
+        self.texture_factory.return_texture(canvas_texture);
+
+        blended_texture
+    }
+
+    pub fn return_texture(&mut self, texture: FactoryTexture) {
+        self.texture_factory.return_texture(texture);
+    }
+}
+
+fn init_clip(clip: &mut Clip, wgpu: &Wgpu, config: &RenderConfiguration, counter: &mut u32) {
+    *counter += 1;
+
+    log::trace!("Initializing clip at {}", clip.range());
+
+    if let Some(video) = clip.video_mut() {
+        video.init(wgpu, config);
+    }
+
+    for effect in clip.effects_mut() {
+        effect.init(wgpu, config);
+    }
+
+    for child in clip.children_mut() {
+        init_clip(child, wgpu, config, counter);
+    }
+
+    if let Some(track_matte) = clip.track_matte_mut() {
+        init_clip(&mut track_matte.source, wgpu, config, counter);
+    }
+}
+
+/// The composited output of a clip, ready to be blended into its parent
+struct RenderedClip {
+    texture: FactoryTexture,
+    opacity: f32,
+    blend_mode: BlendMode,
+    /// Drawn by [`draw_batch`] or by a video that outputs premultiplied alpha
+    premultiplied: bool,
+}
+
+/// Everything `render_clip` needs that stays the same during a frame
+struct RenderContext<'a> {
+    wgpu: &'a Wgpu,
+    encoder: &'a mut wgpu::CommandEncoder,
+    texture_factory: &'a mut TextureFactory,
+    blend_modes: &'a BlendModes,
+    mattes: &'a Mattes,
+    transitions: &'a mut Transitions,
+    /// Absolute transforms of clips with a transform parent
+    parented_transforms: &'a HashMap<ClipId, Mat4>,
+}
+
+impl RenderContext<'_> {
+    fn borrow_texture(&mut self) -> FactoryTexture {
+        self.texture_factory.borrow_texture(self.wgpu)
+    }
+
+    fn borrow_empty_texture(&mut self) -> FactoryTexture {
+        let texture = self.borrow_texture();
+        texture.clear(self.encoder);
+        texture
+    }
+
+    /// Blend `output` onto `canvas_texture`, the result ends up in `blended_texture`
+    fn composite(
+        &mut self,
+        output: RenderedClip,
+        canvas_texture: &mut FactoryTexture,
+        blended_texture: &mut FactoryTexture,
+    ) {
+        // Swap to reuse the textures
+        core::mem::swap(canvas_texture, blended_texture);
+
+        let blend = self.blend_modes.get(output.blend_mode);
+        match output.premultiplied {
+            true => blend.blend_premultiplied(
+                self.wgpu,
+                self.encoder,
+                output.texture.view(),
+                canvas_texture.view(),
+                blended_texture.view(),
+                output.opacity,
+            ),
+            false => blend.blend(
+                self.wgpu,
+                self.encoder,
+                output.texture.view(),
+                canvas_texture.view(),
+                blended_texture.view(),
+                output.opacity,
+            ),
+        }
+
+        self.texture_factory.return_texture(output.texture);
+    }
+}
+
+/// Render the top-level clips of a track, clips in a transition are combined and
+/// rendered in the place of the clip the transition starts from
+fn render_track(
+    track: &mut Track,
+    ctx: &mut RenderContext,
+    frame_info: FrameInfo,
+    project_range: UnboundedTimecodeRange,
+    root_transform: Mat4,
+    canvas_texture: &mut FactoryTexture,
+    blended_texture: &mut FactoryTexture,
+) {
+    let mut active_transitions = Vec::new();
+
+    for transition in track.transitions() {
+        let clips = track.clips();
+        let from = clips.iter().position(|clip| clip.id() == transition.from);
+        let to = clips.iter().position(|clip| clip.id() == transition.to);
+
+        let (Some(from), Some(to)) = (from, to) else {
+            continue;
+        };
+
+        let cut = clips[to]
+            .range()
+            .make_absolute(project_range)
+            .start()
+            .unwrap_or_default();
+
+        if let Some(progress) = transition.progress(cut, frame_info.time_code) {
+            active_transitions.push((from, to, progress as f32, transition.kind.clone()));
+        }
+    }
+
+    let in_transition = |index: usize| {
+        active_transitions
+            .iter()
+            .any(|(from, to, ..)| *from == index || *to == index)
+    };
+
+    let clips = track.clips_mut();
+    let mut index = 0;
+
+    while index < clips.len() {
+        if active_transitions.iter().any(|(_, to, ..)| *to == index) {
+            index += 1;
+            continue;
+        }
+
+        match active_transitions.iter().find(|(from, ..)| *from == index) {
+            Some((from, to, progress, kind)) => {
+                let output = render_transition(
+                    clips,
+                    (*from, *to),
+                    ctx,
+                    frame_info,
+                    project_range,
+                    root_transform,
+                    *progress,
+                    kind,
+                );
+
+                ctx.composite(output, canvas_texture, blended_texture);
+                index += 1;
+            }
+            None => {
+                index += render_next(
+                    &mut clips[index..],
+                    ctx,
+                    frame_info,
+                    project_range,
+                    root_transform,
+                    canvas_texture,
+                    blended_texture,
+                    |offset| in_transition(index + offset),
+                );
+            }
+        }
+    }
+}
+
+/// Render `clips[0]` onto the composite of the clips below it, or draw it in one batch
+/// with the clips after it if they can be. Clips for which `excluded` returns `true`
+/// end a batch. Returns how many clips were rendered.
+#[allow(clippy::too_many_arguments)]
+fn render_next(
+    clips: &mut [Clip],
+    ctx: &mut RenderContext,
+    frame_info: FrameInfo,
+    parent_range: UnboundedTimecodeRange,
+    parent_transform: Mat4,
+    canvas_texture: &mut FactoryTexture,
+    blended_texture: &mut FactoryTexture,
+    excluded: impl Fn(usize) -> bool,
+) -> usize {
+    let batch = clips
+        .iter()
+        .enumerate()
+        .take_while(|(offset, clip)| {
+            !excluded(*offset) && is_batchable(clip, frame_info, parent_range)
+        })
+        .count();
+
+    if batch == 0 {
+        render_child(
+            &mut clips[0],
+            ctx,
+            frame_info,
+            parent_range,
+            parent_transform,
+            canvas_texture,
+            blended_texture,
+        );
+        return 1;
+    }
+
+    if let Some(output) = draw_batch(
+        &mut clips[..batch],
+        ctx,
+        frame_info,
+        parent_range,
+        parent_transform,
+    ) {
+        ctx.composite(output, canvas_texture, blended_texture);
+    }
+
+    batch
+}
+
+/// Whether `clip` is only its video, which can be drawn straight onto a shared layer
+/// without compositing
+fn is_batchable(clip: &Clip, frame_info: FrameInfo, parent_range: UnboundedTimecodeRange) -> bool {
+    let local_frame_info = frame_info.make_local(clip.range().make_absolute(parent_range));
+
+    clip.video().is_some_and(|video| video.batched())
+        && clip.children().is_empty()
+        && clip.masks().is_empty()
+        && clip.effects().is_empty()
+        && clip.track_matte().is_none()
+        && !clip.is_adjustment_layer()
+        && clip.blend_mode() == BlendMode::Normal
+        && clip.opacity_at(local_frame_info.time_code) >= 1.0
+}
+
+/// Draw the videos of batchable clips in a single pass, see [`VisibleObject::draw`](vide_common::visible_object::VisibleObject::draw)
+fn draw_batch(
+    clips: &mut [Clip],
+    ctx: &mut RenderContext,
+    frame_info: FrameInfo,
+    parent_range: UnboundedTimecodeRange,
+    parent_transform: Mat4,
+) -> Option<RenderedClip> {
+    let mut videos = Vec::new();
+
+    for clip in clips {
+        let absolute_range = clip.range().make_absolute(parent_range);
+
+        if !clip.enabled() || clip.is_null() || !absolute_range.contains(frame_info.time_code) {
+            continue;
+        }
+
+        let local_frame_info = frame_info.make_local(absolute_range);
+        let absolute_transform = absolute_transform(clip, ctx, &local_frame_info, parent_transform);
+        let (content_frame_info, content_local_frame_info) =
+            content_frame_info(clip, frame_info, absolute_range, &local_frame_info);
+
+        let video = clip.video_mut().expect("batched clips have a video");
+        video.set_transform(absolute_transform);
+        video.update(ctx.wgpu, &content_frame_info, &content_local_frame_info);
+
+        videos.push((video, content_frame_info, content_local_frame_info));
+    }
+
+    if videos.is_empty() {
+        None?
+    }
+
+    let texture = ctx.borrow_texture();
+
+    let mut pass = ctx.encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+        label: Some("Batch Render Pass"),
+        color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+            view: texture.view(),
+            resolve_target: None,
+            ops: wgpu::Operations {
+                load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                store: wgpu::StoreOp::Store,
+            },
+        })],
+        depth_stencil_attachment: None,
+        timestamp_writes: None,
+        occlusion_query_set: None,
+    });
+
+    for (video, content_frame_info, content_local_frame_info) in videos {
+        video.draw(
+            ctx.wgpu,
+            &content_frame_info,
+            &content_local_frame_info,
+            &mut pass,
+        );
+    }
+
+    drop(pass);
+
+    Some(RenderedClip {
+        texture,
+        opacity: 1.0,
+        blend_mode: BlendMode::Normal,
+        premultiplied: true,
+    })
+}
+
+/// Render a clip and blend it onto the composite of the clips below it, adjustment
+/// layers process that composite instead
+fn render_child(
+    clip: &mut Clip,
+    ctx: &mut RenderContext,
+    frame_info: FrameInfo,
+    parent_range: UnboundedTimecodeRange,
+    parent_transform: Mat4,
+    canvas_texture: &mut FactoryTexture,
+    blended_texture: &mut FactoryTexture,
+) {
+    if clip.is_adjustment_layer() {
+        render_adjustment(
+            clip,
+            ctx,
+            frame_info,
+            parent_range,
+            parent_transform,
+            canvas_texture,
+            blended_texture,
+        );
+        return;
+    }
+
+    if let Some(output) = render_clip(clip, ctx, frame_info, parent_range, parent_transform) {
+        ctx.composite(output, canvas_texture, blended_texture);
+    }
+}
+
+/// Render both clips of a transition, past their in and out points, and combine them
+#[allow(clippy::too_many_arguments)]
+fn render_transition(
+    clips: &mut [Clip],
+    (from, to): (usize, usize),
+    ctx: &mut RenderContext,
+    frame_info: FrameInfo,
+    parent_range: UnboundedTimecodeRange,
+    parent_transform: Mat4,
+    progress: f32,
+    kind: &TransitionKind,
+) -> RenderedClip {
+    let render = |clip: &mut Clip, ctx: &mut RenderContext| {
+        let absolute_range = clip.range().make_absolute(parent_range);
+
+        match clip.enabled() && !clip.is_null() {
+            true => render_clip_in(
+                clip,
+                ctx,
+                frame_info,
+                absolute_range,
+                parent_range,
+                parent_transform,
+            ),
+            false => RenderedClip {
+                texture: ctx.borrow_empty_texture(),
+                opacity: 1.0,
+                blend_mode: BlendMode::Normal,
+                premultiplied: false,
+            },
+        }
+    };
+
+    let from = render(&mut clips[from], ctx);
+    let to = render(&mut clips[to], ctx);
+
+    let texture = ctx.borrow_texture();
+
+    ctx.transitions.apply(
+        ctx.wgpu,
+        ctx.encoder,
+        kind,
+        progress,
+        from.texture.view(),
+        from.opacity,
+        to.texture.view(),
+        to.opacity,
+        texture.view(),
+    );
+
+    ctx.texture_factory.return_texture(from.texture);
+    ctx.texture_factory.return_texture(to.texture);
+
+    RenderedClip {
+        texture,
+        opacity: 1.0,
+        blend_mode: from.blend_mode,
+        premultiplied: false,
+    }
+}
+
+fn has_transform_parent(clip: &Clip) -> bool {
+    clip.transform_parent().is_some()
+        || clip.children().iter().any(has_transform_parent)
+        || clip
+            .track_matte()
+            .is_some_and(|track_matte| has_transform_parent(&track_matte.source))
+}
+
+fn absolute_transform(
+    clip: &Clip,
+    ctx: &RenderContext,
+    local_frame_info: &FrameInfo,
+    parent_transform: Mat4,
+) -> Mat4 {
+    match ctx.parented_transforms.get(&clip.id()) {
+        Some(transform) => *transform,
+        None => parent_transform * clip.transform_at(local_frame_info),
+    }
+}
+
+fn render_clip(
+    clip: &mut Clip,
+    ctx: &mut RenderContext,
+    frame_info: FrameInfo,
+    parent_range: UnboundedTimecodeRange,
+    parent_transform: Mat4,
+) -> Option<RenderedClip> {
+    let absolute_range = clip.range().make_absolute(parent_range);
+
+    if !clip.enabled() || clip.is_null() || !absolute_range.contains(frame_info.time_code) {
+        None?
+    }
+
+    Some(render_clip_in(
+        clip,
+        ctx,
+        frame_info,
+        absolute_range,
+        parent_range,
+        parent_transform,
+    ))
+}
+
+/// The frame info the children and video of a clip see, they get the remapped time
+/// while the clip itself doesn't
+fn content_frame_info(
+    clip: &Clip,
+    frame_info: FrameInfo,
+    absolute_range: UnboundedTimecodeRange,
+    local_frame_info: &FrameInfo,
+) -> (FrameInfo, FrameInfo) {
+    let content_frame_info = FrameInfo {
+        time_code: absolute_range.start().unwrap_or_default()
+            + clip.content_time(local_frame_info.time_code),
+        ..frame_info
+    };
+
+    (
+        content_frame_info,
+        content_frame_info.make_local(absolute_range),
+    )
+}
+
+/// Render a clip placed at `absolute_range`, even if `frame_info` is outside of it
+fn render_clip_in(
+    clip: &mut Clip,
+    ctx: &mut RenderContext,
+    frame_info: FrameInfo,
+    absolute_range: UnboundedTimecodeRange,
+    parent_range: UnboundedTimecodeRange,
+    parent_transform: Mat4,
+) -> RenderedClip {
+    let local_frame_info = frame_info.make_local(absolute_range);
+    let absolute_transform = absolute_transform(clip, ctx, &local_frame_info, parent_transform);
+
+    let (content_frame_info, content_local_frame_info) =
+        content_frame_info(clip, frame_info, absolute_range, &local_frame_info);
+
+    let mut canvas_texture = ctx.borrow_texture();
+    let mut blended_texture = ctx.borrow_texture();
+
+    blended_texture.clear(ctx.encoder);
+
+    let content_range = clip.content_range(absolute_range);
+    let children = clip.children_mut();
+    let mut index = 0;
+
+    while index < children.len() {
+        index += render_next(
+            &mut children[index..],
+            ctx,
+            content_frame_info,
+            content_range,
+            absolute_transform,
+            &mut canvas_texture,
+            &mut blended_texture,
+            |_| false,
+        );
+    }
+
+    if let Some(video) = clip.video_mut() {
+        let output_texture = ctx.borrow_texture();
+
+        video.set_transform(absolute_transform);
+        video.update(ctx.wgpu, &content_frame_info, &content_local_frame_info);
+        video.render(
+            ctx.wgpu,
+            &content_frame_info,
+            &content_local_frame_info,
+            ctx.encoder,
+            output_texture.view(),
+        );
+
+        ctx.composite(
+            RenderedClip {
+                texture: output_texture,
+                opacity: 1.0,
+                blend_mode: BlendMode::Normal,
+                premultiplied: video.premultiplied(),
+            },
+            &mut canvas_texture,
+            &mut blended_texture,
+        );
+    }
+
+    ctx.texture_factory.return_texture(canvas_texture);
+
+    let mut texture = blended_texture;
+
+    let animation_frame_info = clip.animation_frame_info(&local_frame_info);
+
+    if !clip.masks().is_empty() {
+        texture = apply_masks(
+            ctx,
+            clip.masks(),
+            absolute_transform,
+            &animation_frame_info,
+            texture,
+        );
+    }
+
+    if let Some(output_texture) = apply_effects(
+        clip.effects_mut(),
+        ctx,
+        &frame_info,
+        &animation_frame_info,
+        texture.view(),
+    ) {
+        ctx.texture_factory.return_texture(texture);
+        texture = output_texture;
+    }
+
+    texture = apply_track_matte(
+        clip,
+        ctx,
+        frame_info,
+        parent_range,
+        parent_transform,
+        texture,
+    );
+
+    RenderedClip {
+        texture,
+        opacity: clip.opacity_at(local_frame_info.time_code) as f32,
+        blend_mode: clip.blend_mode(),
+        premultiplied: false,
+    }
+}
+
+/// Run the effects of an adjustment layer over the composite of the clips below it in
+/// `blended_texture`. The result replaces the composite where the masks and track matte
+/// of the layer are visible, scaled by its opacity. The children, video and blend mode
+/// of an adjustment layer are ignored.
+#[allow(clippy::too_many_arguments)]
+fn render_adjustment(
+    clip: &mut Clip,
+    ctx: &mut RenderContext,
+    frame_info: FrameInfo,
+    parent_range: UnboundedTimecodeRange,
+    parent_transform: Mat4,
+    canvas_texture: &mut FactoryTexture,
+    blended_texture: &mut FactoryTexture,
+) {
+    let absolute_range = clip.range().make_absolute(parent_range);
+
+    if !clip.enabled() || !absolute_range.contains(frame_info.time_code) {
+        return;
+    }
+
+    let local_frame_info = frame_info.make_local(absolute_range);
+    let absolute_transform = absolute_transform(clip, ctx, &local_frame_info, parent_transform);
+    let animation_frame_info = clip.animation_frame_info(&local_frame_info);
+
+    let Some(adjusted_texture) = apply_effects(
+        clip.effects_mut(),
+        ctx,
+        &frame_info,
+        &animation_frame_info,
+        blended_texture.view(),
+    ) else {
+        return;
+    };
+
+    // Where the adjustment is visible, the masks and matte cut out of a full coverage
+    let mut coverage_texture = ctx.borrow_texture();
+    coverage_texture.fill(ctx.encoder, wgpu::Color::WHITE);
+
+    if !clip.masks().is_empty() {
+        coverage_texture = apply_masks(
+            ctx,
+            clip.masks(),
+            absolute_transform,
+            &animation_frame_info,
+            coverage_texture,
+        );
+    }
+
+    coverage_texture = apply_track_matte(
+        clip,
+        ctx,
+        frame_info,
+        parent_range,
+        parent_transform,
+        coverage_texture,
+    );
+
+    // Swap to reuse the textures, like `RenderContext::composite`
+    core::mem::swap(canvas_texture, blended_texture);
+
+    ctx.mattes.mix(
+        ctx.wgpu,
+        ctx.encoder,
+        canvas_texture.view(),
+        adjusted_texture.view(),
+        coverage_texture.view(),
+        blended_texture.view(),
+        clip.opacity_at(local_frame_info.time_code) as f32,
+    );
+
+    ctx.texture_factory.return_texture(adjusted_texture);
+    ctx.texture_factory.return_texture(coverage_texture);
+}
+
+/// Run `effects` one after another starting from `source`, returns `None` if there are
+/// no effects
+fn apply_effects(
+    effects: &mut [Box<dyn Effect>],
+    ctx: &mut RenderContext,
+    frame_info: &FrameInfo,
+    local_frame_info: &FrameInfo,
+    source: &wgpu::TextureView,
+) -> Option<FactoryTexture> {
+    let mut previous_texture: Option<FactoryTexture> = None;
+
+    for effect in effects {
+        let output_texture = ctx.borrow_texture();
+
+        effect.apply(
+            ctx.wgpu,
+            frame_info,
+            local_frame_info,
+            ctx.encoder,
+            previous_texture
+                .as_ref()
+                .map_or(source, |texture| texture.view()),
+            output_texture.view(),
+        );
+
+        if let Some(texture) = previous_texture.replace(output_texture) {
+            ctx.texture_factory.return_texture(texture);
+        }
+    }
+
+    previous_texture
+}
+
+fn apply_track_matte(
+    clip: &mut Clip,
+    ctx: &mut RenderContext,
+    frame_info: FrameInfo,
+    parent_range: UnboundedTimecodeRange,
+    parent_transform: Mat4,
+    texture: FactoryTexture,
+) -> FactoryTexture {
+    let Some(track_matte) = clip.track_matte_mut() else {
+        return texture;
+    };
+
+    let matte = render_clip(
+        &mut track_matte.source,
+        ctx,
+        frame_info,
+        parent_range,
+        parent_transform,
+    );
+
+    // A matte that isn't visible right now still cuts out the clip
+    let (matte_texture, matte_opacity) = match matte {
+        Some(matte) => (matte.texture, matte.opacity),
+        None => (ctx.borrow_empty_texture(), 1.0),
+    };
+
+    let matted_texture = ctx.borrow_texture();
+
+    ctx.mattes.apply(
+        ctx.wgpu,
+        ctx.encoder,
+        texture.view(),
+        matte_texture.view(),
+        matted_texture.view(),
+        track_matte.mode,
+        matte_opacity,
+    );
+
+    ctx.texture_factory.return_texture(matte_texture);
+    ctx.texture_factory.return_texture(texture);
+
+    matted_texture
+}
+
+fn apply_masks(
+    ctx: &mut RenderContext,
+    masks: &[Mask],
+    transform: Mat4,
+    local_frame_info: &FrameInfo,
+    texture: FactoryTexture,
+) -> FactoryTexture {
+    let mut previous_texture = ctx.borrow_texture();
+    let mut coverage_texture = ctx.borrow_texture();
+
+    // Masks that remove coverage start from a fully visible clip
+    match masks.first().map(|mask| mask.mode) {
+        Some(MaskMode::Add) => coverage_texture.clear(ctx.encoder),
+        _ => coverage_texture.fill(ctx.encoder, wgpu::Color::WHITE),
+    }
+
+    for mask in masks {
+        core::mem::swap(&mut previous_texture, &mut coverage_texture);
+
+        ctx.mattes.mask(
+            ctx.wgpu,
+            ctx.encoder,
+            mask,
+            transform,
+            local_frame_info,
+            previous_texture.view(),
+            coverage_texture.view(),
+        );
+    }
+
+    let masked_texture = ctx.borrow_texture();
+
+    ctx.mattes.apply(
+        ctx.wgpu,
+        ctx.encoder,
+        texture.view(),
+        coverage_texture.view(),
+        masked_texture.view(),
+        MatteMode::Alpha,
+        1.0,
+    );
+
+    ctx.texture_factory.return_texture(previous_texture);
+    ctx.texture_factory.return_texture(coverage_texture);
+    ctx.texture_factory.return_texture(texture);
+
+    masked_texture
+}
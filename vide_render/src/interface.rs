@@ -1,9 +1,17 @@
 use vide_common::{config::RenderConfiguration, render::Wgpu, FrameInfo};
+use vide_project::marker::Chapter;
 
 use crate::texture_factory::FactoryTexture;
 
 pub trait OutputHandler {
+    /// Called before [`OutputHandler::configure`] with the chapters of the project, for
+    /// outputs that can store them
+    fn set_chapters(&mut self, chapters: &[Chapter]) {
+        let _ = chapters;
+    }
     fn configure(&mut self, wgpu: &Wgpu, config: &RenderConfiguration) -> wgpu::TextureFormat;
+    /// `frame` counts the published frames from 0, `frame_info.time_code` starts at 0
+    /// at the start of the rendered range
     fn publish_frame(
         &mut self,
         wgpu: &Wgpu,
@@ -1,24 +1,27 @@
 use blend::BlendModes;
-use euler::{mat4, Mat4};
+use compositor::Compositor;
+use euler::{mat4, vec3, Mat4, Quat, Trs};
 use interface::OutputHandler;
 use pollster::FutureExt;
-use texture_factory::{FactoryTexture, TextureFactory};
+use texture_factory::TextureFactory;
 use vide_common::{
-    config::RenderConfiguration,
-    prelude::TimeCode,
-    render::{GlobalUniform, Wgpu},
-    standards::FRAGMENT_COLOR_TARGET,
-    time_code::UnboundedTimecodeRange,
-    types::TimeUnit,
+    config::{RenderConfiguration, Strictness},
+    render::{GlobalUniform, ResourceCache, Wgpu},
+    types::{Frames, TimeUnit},
     FrameInfo,
 };
-use vide_project::{clip::Clip, Project};
+use vide_project::{canvas::Canvas, validate::Severity, Project};
 use wgpu::util::DeviceExt;
 
+pub mod batch;
 pub mod blend;
+pub mod compositor;
 pub mod export;
 pub mod interface;
+pub mod matte;
+mod parenting;
 pub mod texture_factory;
+pub mod transition;
 
 pub async fn init_wgpu() -> Wgpu {
     let instance = wgpu::Instance::new(wgpu::InstanceDescriptor::default());
@@ -86,106 +89,10 @@ pub async fn init_wgpu() -> Wgpu {
         queue,
         global_bind_group_layout,
         global_bind_group,
+        resources: ResourceCache::new(),
     }
 }
 
-fn init_clip(clip: &mut Clip, wgpu: &Wgpu, config: &RenderConfiguration, counter: &mut u32) {
-    *counter += 1;
-
-    log::trace!("Initializing clip at {}", clip.range());
-
-    if let Some(video) = clip.video_mut() {
-        video.init(wgpu, config);
-    }
-
-    for child in clip.children_mut() {
-        init_clip(child, wgpu, config, counter);
-    }
-}
-
-fn render_clip(
-    clip: &mut Clip,
-    wgpu: &Wgpu,
-    frame_info: FrameInfo,
-    parent_range: UnboundedTimecodeRange,
-    parent_transform: Mat4,
-    encoder: &mut wgpu::CommandEncoder,
-    texture_factory: &mut TextureFactory,
-    blend_modes: &BlendModes,
-) -> Option<FactoryTexture> {
-    let absolute_range = clip.range().make_absolute(parent_range);
-
-    if !absolute_range.contains(frame_info.time_code) {
-        None?
-    }
-
-    let absolute_transform = parent_transform * clip.transform().matrix();
-
-    let mut canvas_texture = texture_factory.borrow_texture(wgpu);
-    let mut blended_texture = texture_factory.borrow_texture(wgpu);
-
-    for child in clip.children_mut() {
-        let output = render_clip(
-            child,
-            wgpu,
-            frame_info,
-            absolute_range,
-            absolute_transform,
-            encoder,
-            texture_factory,
-            blend_modes,
-        );
-
-        if let Some(output) = output {
-            // Swap to reuse the textures
-            core::mem::swap(&mut canvas_texture, &mut blended_texture);
-
-            blend_modes.normal.blend(
-                wgpu,
-                encoder,
-                output.view(),
-                canvas_texture.view(),
-                blended_texture.view(),
-            );
-
-            texture_factory.return_texture(output);
-        }
-    }
-
-    let local_frame_info = frame_info.make_local(absolute_range);
-
-    if let Some(video) = clip.video_mut() {
-        // Swap to reuse the textures
-        core::mem::swap(&mut canvas_texture, &mut blended_texture);
-
-        let output_texture = texture_factory.borrow_texture(wgpu);
-
-        video.set_transform(absolute_transform);
-        video.update(wgpu, &frame_info, &local_frame_info);
-        video.render(
-            wgpu,
-            &frame_info,
-            &local_frame_info,
-            encoder,
-            output_texture.view(),
-        );
-
-        blend_modes.normal.blend(
-            wgpu,
-            encoder,
-            output_texture.view(),
-            canvas_texture.view(),
-            blended_texture.view(),
-        );
-
-        texture_factory.return_texture(output_texture);
-    }
-
-    texture_factory.return_texture(canvas_texture);
-
-    Some(blended_texture)
-}
-
 fn generate_ortho_matrix(config: &RenderConfiguration) -> Mat4 {
     let width = config.resolution.0 as f32;
     let height = config.resolution.1 as f32;
@@ -205,13 +112,81 @@ fn generate_ortho_matrix(config: &RenderConfiguration) -> Mat4 {
     matrix
 }
 
-pub fn render(mut project: Project, config: RenderConfiguration, mut output: impl OutputHandler) {
+/// The transform that lays out clips on the canvas and the region of the output
+/// (`x, y, width, height` from the top left) the canvas covers
+fn map_canvas(canvas: Canvas, config: &RenderConfiguration) -> (Mat4, [u32; 4]) {
+    let (width, height) = config.resolution;
+    let ((scale_x, scale_y), (offset_x, offset_y)) =
+        config.scaling.map(canvas.size, config.resolution);
+
+    let transform = Trs::new(
+        vec3!(offset_x as f32, offset_y as f32, 0.0),
+        Quat::identity(),
+        vec3!(scale_x as f32, scale_y as f32, 1.0),
+    )
+    .matrix();
+
+    // The ortho matrix has its origin at the bottom left
+    let left = offset_x.round().clamp(0.0, width as f64) as u32;
+    let right = (offset_x + canvas.size.0 as f64 * scale_x)
+        .round()
+        .clamp(0.0, width as f64) as u32;
+    let bottom = (height as f64 - offset_y).round().clamp(0.0, height as f64) as u32;
+    let top = (height as f64 - offset_y - canvas.size.1 as f64 * scale_y)
+        .round()
+        .clamp(0.0, height as f64) as u32;
+
+    (transform, [left, top, right - left, bottom - top])
+}
+
+pub fn render(project: Project, config: RenderConfiguration, mut output: impl OutputHandler) {
     let _ = env_logger::try_init();
 
     log::info!("Initializing wgpu");
 
     let mut wgpu = init_wgpu().block_on();
 
+    render_with(&mut wgpu, project, config, &mut output);
+}
+
+/// Log the problems [`Project::validate`] finds, panics if `strictness` denies them
+fn validate_project(project: &Project, strictness: Strictness) {
+    if strictness == Strictness::Off {
+        return;
+    }
+
+    log::info!("Validating project");
+
+    let diagnostics = project.validate();
+    let mut denied = 0;
+
+    for diagnostic in &diagnostics {
+        match diagnostic.severity {
+            Severity::Warning => log::warn!("{diagnostic}"),
+            Severity::Error => log::error!("{diagnostic}"),
+        }
+
+        if diagnostic.severity == Severity::Error || strictness == Strictness::DenyWarnings {
+            denied += 1;
+        }
+    }
+
+    if strictness != Strictness::Warn && denied > 0 {
+        panic!("Project validation failed with {denied} problem(s), see the log for details");
+    }
+}
+
+/// Render a project using an already initialized [`Wgpu`] instance, this allows
+/// reusing the same device for multiple renders
+pub fn render_with(
+    wgpu: &mut Wgpu,
+    mut project: Project,
+    config: RenderConfiguration,
+    output: &mut dyn OutputHandler,
+) {
+    project.infer_durations();
+    validate_project(&project, config.strictness);
+
     let global_uniform_buffer = wgpu
         .device
         .create_buffer_init(&wgpu::util::BufferInitDescriptor {
@@ -231,21 +206,25 @@ pub fn render(mut project: Project, config: RenderConfiguration, mut output: imp
         }],
     });
 
-    {
-        log::info!("Initializing clips");
+    log::info!("Initializing clips");
 
-        let mut counter = 0u32;
+    let counter = Compositor::init_project(wgpu, &mut project, &config);
 
-        for clip in project.clips_mut() {
-            init_clip(clip, &wgpu, &config, &mut counter);
-        }
+    log::info!("Initialized {counter} clips");
 
-        log::info!("Initialized {counter} clips");
-    }
+    let frames = project.frame_count(config.frames_per_second);
+    let range = project.frame_range(config.range, config.frames_per_second);
 
     log::info!("Configuring output handler");
 
-    let output_format = output.configure(&wgpu, &config);
+    // Chapters are in the time of the output, which starts at the range
+    let to_time_code = |frame: Frames| TimeUnit::Seconds(frame as f64 / config.frames_per_second);
+    output.set_chapters(&project.chapters_in(
+        to_time_code(range.start).into(),
+        to_time_code(range.end).into(),
+    ));
+
+    let output_format = output.configure(wgpu, &config);
 
     log::info!("Initializing texture factories and output textures");
 
@@ -269,102 +248,98 @@ pub fn render(mut project: Project, config: RenderConfiguration, mut output: imp
         wgpu::TextureViewDescriptor::default(),
     );
 
-    let handler_canvas_texture = handler_texture_factory.borrow_texture(&wgpu);
-    let handler_blended_texture = handler_texture_factory.borrow_texture(&wgpu);
-
-    let mut texture_factory = TextureFactory::new(
-        wgpu::TextureDescriptor {
-            label: Some("OutputHandler read texture"),
-            dimension: wgpu::TextureDimension::D2,
-            format: FRAGMENT_COLOR_TARGET,
-            mip_level_count: 1,
-            sample_count: 1,
-            size: wgpu::Extent3d {
-                width: config.resolution.0 as u32,
-                height: config.resolution.1 as u32,
-                depth_or_array_layers: 1,
-            },
-            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
-            view_formats: &[],
-        },
-        wgpu::TextureViewDescriptor::default(),
-    );
-
-    let blend_modes = BlendModes::load(&wgpu, FRAGMENT_COLOR_TARGET);
-    let blend_modes_root = BlendModes::load(&wgpu, output_format);
+    let handler_canvas_texture = handler_texture_factory.borrow_texture(wgpu);
+    let handler_blended_texture = handler_texture_factory.borrow_texture(wgpu);
 
-    let project_range =
-        UnboundedTimecodeRange::new(Some(TimeCode::new(0)), Some(project.duration()));
-    let frames = project.frame_count(config.frames_per_second);
+    let mut compositor = Compositor::new(wgpu, config.resolution);
 
-    log::info!("Starting render ({frames} frames)");
+    let failed = compositor.prepare_transitions(wgpu, &project);
+    if failed > 0
+        && matches!(
+            config.strictness,
+            Strictness::Deny | Strictness::DenyWarnings
+        )
+    {
+        panic!("{failed} custom transition(s) failed to compile, see the log for details");
+    }
+    let blend_modes_root = BlendModes::load(wgpu, output_format);
+
+    let canvas = project
+        .canvas()
+        .unwrap_or_else(|| Canvas::new(config.resolution));
+    let (canvas_transform, canvas_region) = map_canvas(canvas, &config);
+    let background = canvas.background.into();
+    let bars = config.scaling.bars().into();
+
+    let rendered = range.clone().step_by(config.frame_step.max(1) as usize);
+    let rendered_count = rendered.clone().count();
+
+    log::info!(
+        "Starting render ({rendered_count} of {frames} frames, {}..{})",
+        range.start,
+        range.end
+    );
 
-    for frame in 0..frames {
+    for (output_frame, frame) in rendered.enumerate() {
         log::trace!("Rendering frame {frame}");
 
         let mut encoder = wgpu
             .device
             .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
 
-        let time_code = TimeUnit::Seconds(frame as f64 / config.frames_per_second).into();
-        let progress = frame as f64 / frames as f64;
-
-        let mut canvas_texture = texture_factory.borrow_texture(&wgpu);
-        let mut blended_texture = texture_factory.borrow_texture(&wgpu);
+        // The project is evaluated at its own time, the output starts at the range
+        let project_frame_info = FrameInfo {
+            time_code: TimeUnit::Seconds(frame as f64 / config.frames_per_second).into(),
+            progress: frame as f64 / frames as f64,
+            resolution: canvas.size,
+        };
 
         let frame_info = FrameInfo {
-            time_code,
-            progress,
+            time_code: TimeUnit::Seconds((frame - range.start) as f64 / config.frames_per_second)
+                .into(),
+            progress: output_frame as f64 / rendered_count as f64,
             resolution: config.resolution,
         };
 
-        for clip in project.clips_mut() {
-            let output = render_clip(
-                clip,
-                &wgpu,
-                frame_info,
-                project_range,
-                Mat4::identity(),
-                &mut encoder,
-                &mut texture_factory,
-                &blend_modes,
-            );
-
-            if let Some(output) = output {
-                // Swap to reuse textures
-                core::mem::swap(&mut canvas_texture, &mut blended_texture);
-
-                blend_modes.normal.blend(
-                    &wgpu,
-                    &mut encoder,
-                    output.view(),
-                    canvas_texture.view(),
-                    blended_texture.view(),
-                );
+        let blended_texture = compositor.render_frame(
+            wgpu,
+            &mut encoder,
+            &mut project,
+            project_frame_info,
+            canvas_transform,
+        );
 
-                texture_factory.return_texture(output);
-            }
-        }
+        handler_canvas_texture.fill(&mut encoder, background);
+        handler_blended_texture.fill(&mut encoder, bars);
 
-        blend_modes_root.normal.blend(
-            &wgpu,
+        blend_modes_root.normal.blend_region(
+            wgpu,
             &mut encoder,
             blended_texture.view(),
             handler_canvas_texture.view(),
             handler_blended_texture.view(),
+            1.0,
+            canvas_region,
         );
 
-        texture_factory.return_texture(canvas_texture);
-        texture_factory.return_texture(blended_texture);
+        compositor.return_texture(blended_texture);
 
-        output.publish_frame(&wgpu, encoder, &handler_blended_texture, frame, frame_info);
+        output.publish_frame(
+            wgpu,
+            encoder,
+            &handler_blended_texture,
+            output_frame as i64,
+            frame_info,
+        );
     }
 
-    output.finish(&wgpu);
+    output.finish(wgpu);
 
     handler_texture_factory.return_texture(handler_canvas_texture);
     handler_texture_factory.return_texture(handler_blended_texture);
 
+    let texture_factory = compositor.texture_factory();
+
     #[rustfmt::skip]
     {
         log::info!("Finished render");
@@ -387,3 +362,58 @@ pub fn render(mut project: Project, config: RenderConfiguration, mut output: imp
         }
     };
 }
+
+#[cfg(test)]
+mod tests {
+    use euler::{vec4, Vec4};
+    use vide_common::{config::Scaling, types::Resolution};
+
+    use super::*;
+
+    fn map(canvas: Resolution, scaling: Scaling) -> (Mat4, [u32; 4]) {
+        map_canvas(
+            Canvas::new(canvas),
+            &RenderConfiguration {
+                resolution: (100, 100),
+                scaling,
+                ..Default::default()
+            },
+        )
+    }
+
+    /// Where a point of the canvas ends up in the output, from the bottom left
+    fn output_point(transform: Mat4, x: f32, y: f32) -> (f32, f32) {
+        let point: Vec4 = transform * vec4!(x, y, 0.0, 1.0);
+        (point.x, point.y)
+    }
+
+    #[test]
+    fn wide_canvas() {
+        let (transform, region) = map((200, 100), Scaling::Fit);
+        assert_eq!(region, [0, 25, 100, 50]);
+        assert_eq!(output_point(transform, 0.0, 0.0), (0.0, 25.0));
+        assert_eq!(output_point(transform, 200.0, 100.0), (100.0, 75.0));
+
+        let (transform, region) = map((200, 100), Scaling::Fill);
+        assert_eq!(region, [0, 0, 100, 100]);
+        assert_eq!(output_point(transform, 50.0, 0.0), (0.0, 0.0));
+        assert_eq!(output_point(transform, 150.0, 100.0), (100.0, 100.0));
+
+        let (transform, region) = map((200, 100), Scaling::Stretch);
+        assert_eq!(region, [0, 0, 100, 100]);
+        assert_eq!(output_point(transform, 200.0, 100.0), (100.0, 100.0));
+    }
+
+    #[test]
+    fn narrow_canvas() {
+        let (transform, region) = map((100, 200), Scaling::Letterbox(Default::default()));
+        assert_eq!(region, [25, 0, 50, 100]);
+        assert_eq!(output_point(transform, 0.0, 0.0), (25.0, 0.0));
+        assert_eq!(output_point(transform, 100.0, 200.0), (75.0, 100.0));
+
+        let (transform, region) = map((100, 200), Scaling::Fill);
+        assert_eq!(region, [0, 0, 100, 100]);
+        assert_eq!(output_point(transform, 0.0, 50.0), (0.0, 0.0));
+        assert_eq!(output_point(transform, 100.0, 150.0), (100.0, 100.0));
+    }
+}
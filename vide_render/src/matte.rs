@@ -0,0 +1,477 @@
+use euler::Mat4;
+use vide_common::{render::Wgpu, FrameInfo};
+use vide_project::matte::{Mask, MaskMode, MaskShape, MatteMode};
+use wgpu::util::DeviceExt;
+
+const MATTE_VERTEX_SOURCE: &str = include_str!("./shaders/blend/vertex.wgsl");
+
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy)]
+struct MatteUniform {
+    mode: u32,
+    opacity: f32,
+    _padding: [f32; 2],
+}
+
+unsafe impl bytemuck::Pod for MatteUniform {}
+unsafe impl bytemuck::Zeroable for MatteUniform {}
+
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy)]
+struct MixUniform {
+    opacity: f32,
+    _padding: [f32; 3],
+}
+
+unsafe impl bytemuck::Pod for MixUniform {}
+unsafe impl bytemuck::Zeroable for MixUniform {}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+struct MaskUniform {
+    inverse_transform: [[f32; 4]; 4],
+    center: [f32; 2],
+    half_size: [f32; 2],
+    _padding: [f32; 2],
+    roundness: f32,
+    feather: f32,
+    expansion: f32,
+    shape: u32,
+    mode: u32,
+    inverted: u32,
+}
+
+unsafe impl bytemuck::Pod for MaskUniform {}
+unsafe impl bytemuck::Zeroable for MaskUniform {}
+
+pub(crate) fn texture_entry(binding: u32) -> wgpu::BindGroupLayoutEntry {
+    wgpu::BindGroupLayoutEntry {
+        binding,
+        visibility: wgpu::ShaderStages::FRAGMENT,
+        ty: wgpu::BindingType::Texture {
+            sample_type: wgpu::TextureSampleType::Float { filterable: true },
+            view_dimension: wgpu::TextureViewDimension::D2,
+            multisampled: false,
+        },
+        count: None,
+    }
+}
+
+pub(crate) fn sampler_entry(binding: u32) -> wgpu::BindGroupLayoutEntry {
+    wgpu::BindGroupLayoutEntry {
+        binding,
+        visibility: wgpu::ShaderStages::FRAGMENT,
+        ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+        count: None,
+    }
+}
+
+pub(crate) fn uniform_entry(binding: u32) -> wgpu::BindGroupLayoutEntry {
+    wgpu::BindGroupLayoutEntry {
+        binding,
+        visibility: wgpu::ShaderStages::FRAGMENT,
+        ty: wgpu::BindingType::Buffer {
+            ty: wgpu::BufferBindingType::Uniform,
+            has_dynamic_offset: false,
+            min_binding_size: None,
+        },
+        count: None,
+    }
+}
+
+pub(crate) fn create_pipeline(
+    wgpu: &Wgpu,
+    label: &str,
+    target: wgpu::TextureFormat,
+    bind_group_layouts: &[&wgpu::BindGroupLayout],
+    fragment: &str,
+) -> wgpu::RenderPipeline {
+    let vertex_module = wgpu
+        .device
+        .create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Matte Vertex Shader"),
+            source: wgpu::ShaderSource::Wgsl(MATTE_VERTEX_SOURCE.into()),
+        });
+
+    let fragment_module = wgpu
+        .device
+        .create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some(label),
+            source: wgpu::ShaderSource::Wgsl(fragment.into()),
+        });
+
+    let pipeline_layout = wgpu
+        .device
+        .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some(label),
+            bind_group_layouts,
+            push_constant_ranges: &[],
+        });
+
+    wgpu.device
+        .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some(label),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &vertex_module,
+                entry_point: None,
+                compilation_options: Default::default(),
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &fragment_module,
+                entry_point: None,
+                compilation_options: Default::default(),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: target,
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::all(),
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: Some(wgpu::Face::Back),
+                unclipped_depth: false,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                conservative: false,
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState {
+                count: 1,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+            cache: None,
+        })
+}
+
+pub(crate) fn full_screen_pass(
+    encoder: &mut wgpu::CommandEncoder,
+    label: &str,
+    pipeline: &wgpu::RenderPipeline,
+    bind_groups: &[&wgpu::BindGroup],
+    target: &wgpu::TextureView,
+) {
+    let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+        label: Some(label),
+        color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+            view: target,
+            resolve_target: None,
+            ops: wgpu::Operations {
+                load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                store: wgpu::StoreOp::Store,
+            },
+        })],
+        depth_stencil_attachment: None,
+        timestamp_writes: None,
+        occlusion_query_set: None,
+    });
+
+    pass.set_pipeline(pipeline);
+    for (index, bind_group) in bind_groups.iter().enumerate() {
+        pass.set_bind_group(index as u32, *bind_group, &[]);
+    }
+    pass.draw(0..3, 0..1);
+}
+
+/// Pipelines for track mattes and masks
+#[derive(Debug)]
+pub struct Mattes {
+    sampler: wgpu::Sampler,
+
+    apply_bind_group_layout: wgpu::BindGroupLayout,
+    apply_pipeline: wgpu::RenderPipeline,
+
+    mask_bind_group_layout: wgpu::BindGroupLayout,
+    mask_pipeline: wgpu::RenderPipeline,
+
+    mix_bind_group_layout: wgpu::BindGroupLayout,
+    mix_pipeline: wgpu::RenderPipeline,
+}
+
+impl Mattes {
+    pub fn load(wgpu: &Wgpu, target: wgpu::TextureFormat) -> Self {
+        log::info!("Loading mattes for {target:?}");
+
+        let sampler = wgpu.device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Matte Sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToBorder,
+            address_mode_v: wgpu::AddressMode::ClampToBorder,
+            address_mode_w: wgpu::AddressMode::ClampToBorder,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Linear,
+            lod_min_clamp: 0.0,
+            lod_max_clamp: 32.0,
+            compare: None,
+            anisotropy_clamp: 1,
+            border_color: Some(wgpu::SamplerBorderColor::TransparentBlack),
+        });
+
+        let apply_bind_group_layout =
+            wgpu.device
+                .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                    label: Some("Matte Apply Bind Group Layout"),
+                    entries: &[
+                        texture_entry(0),
+                        texture_entry(1),
+                        sampler_entry(2),
+                        uniform_entry(3),
+                    ],
+                });
+
+        let apply_pipeline = create_pipeline(
+            wgpu,
+            "Matte Apply Pipeline",
+            target,
+            &[&apply_bind_group_layout],
+            include_str!("./shaders/matte/apply.wgsl"),
+        );
+
+        let mask_bind_group_layout =
+            wgpu.device
+                .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                    label: Some("Mask Bind Group Layout"),
+                    entries: &[texture_entry(0), sampler_entry(1), uniform_entry(2)],
+                });
+
+        let mask_pipeline = create_pipeline(
+            wgpu,
+            "Mask Pipeline",
+            target,
+            // The global ortho matrix maps fragments back to the space of the transforms
+            &[&mask_bind_group_layout, &wgpu.global_bind_group_layout],
+            include_str!("./shaders/matte/mask.wgsl"),
+        );
+
+        let mix_bind_group_layout =
+            wgpu.device
+                .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                    label: Some("Matte Mix Bind Group Layout"),
+                    entries: &[
+                        texture_entry(0),
+                        texture_entry(1),
+                        texture_entry(2),
+                        sampler_entry(3),
+                        uniform_entry(4),
+                    ],
+                });
+
+        let mix_pipeline = create_pipeline(
+            wgpu,
+            "Matte Mix Pipeline",
+            target,
+            &[&mix_bind_group_layout],
+            include_str!("./shaders/matte/mix.wgsl"),
+        );
+
+        Self {
+            sampler,
+            apply_bind_group_layout,
+            apply_pipeline,
+            mask_bind_group_layout,
+            mask_pipeline,
+            mix_bind_group_layout,
+            mix_pipeline,
+        }
+    }
+
+    /// Cut out `source` using `matte` and write the result to `target`
+    #[allow(clippy::too_many_arguments)]
+    pub fn apply(
+        &self,
+        wgpu: &Wgpu,
+        encoder: &mut wgpu::CommandEncoder,
+        source: &wgpu::TextureView,
+        matte: &wgpu::TextureView,
+        target: &wgpu::TextureView,
+        mode: MatteMode,
+        opacity: f32,
+    ) {
+        let uniform_buffer = wgpu
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Matte Uniform Buffer"),
+                contents: bytemuck::cast_slice(&[MatteUniform {
+                    mode: match mode {
+                        MatteMode::Alpha => 0,
+                        MatteMode::InvertedAlpha => 1,
+                        MatteMode::Luma => 2,
+                        MatteMode::InvertedLuma => 3,
+                    },
+                    opacity,
+                    ..Default::default()
+                }]),
+                usage: wgpu::BufferUsages::UNIFORM,
+            });
+
+        let bind_group = wgpu.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Matte Apply Bind Group"),
+            layout: &self.apply_bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(source),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(matte),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::Sampler(&self.sampler),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: uniform_buffer.as_entire_binding(),
+                },
+            ],
+        });
+
+        full_screen_pass(
+            encoder,
+            "Matte Apply Render Pass",
+            &self.apply_pipeline,
+            &[&bind_group],
+            target,
+        );
+    }
+
+    /// Replace `from` with `to` where `coverage` (its alpha channel) is visible, scaled
+    /// by `opacity`, and write the result to `target`
+    #[allow(clippy::too_many_arguments)]
+    pub fn mix(
+        &self,
+        wgpu: &Wgpu,
+        encoder: &mut wgpu::CommandEncoder,
+        from: &wgpu::TextureView,
+        to: &wgpu::TextureView,
+        coverage: &wgpu::TextureView,
+        target: &wgpu::TextureView,
+        opacity: f32,
+    ) {
+        let uniform_buffer = wgpu
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Matte Mix Uniform Buffer"),
+                contents: bytemuck::cast_slice(&[MixUniform {
+                    opacity,
+                    ..Default::default()
+                }]),
+                usage: wgpu::BufferUsages::UNIFORM,
+            });
+
+        let bind_group = wgpu.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Matte Mix Bind Group"),
+            layout: &self.mix_bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(from),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(to),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::TextureView(coverage),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: wgpu::BindingResource::Sampler(&self.sampler),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 4,
+                    resource: uniform_buffer.as_entire_binding(),
+                },
+            ],
+        });
+
+        full_screen_pass(
+            encoder,
+            "Matte Mix Render Pass",
+            &self.mix_pipeline,
+            &[&bind_group],
+            target,
+        );
+    }
+
+    /// Combine the coverage of `mask` with `previous` and write it to `target`. The
+    /// coverage is stored in the alpha channel so it can be used as an alpha matte.
+    #[allow(clippy::too_many_arguments)]
+    pub fn mask(
+        &self,
+        wgpu: &Wgpu,
+        encoder: &mut wgpu::CommandEncoder,
+        mask: &Mask,
+        transform: Mat4,
+        local_frame_info: &FrameInfo,
+        previous: &wgpu::TextureView,
+        target: &wgpu::TextureView,
+    ) {
+        let time_code = local_frame_info.time_code;
+        let resolution = local_frame_info.resolution;
+
+        let center = mask.position.evaluate(time_code).resolve(resolution);
+        let size = mask.size.evaluate(time_code).resolve(resolution);
+
+        let uniform = MaskUniform {
+            inverse_transform: transform.try_invert().unwrap_or_default().into(),
+            center: center.into(),
+            half_size: (size / 2.0).into(),
+            _padding: [0.0; 2],
+            roundness: mask.roundness.evaluate(time_code) as f32,
+            feather: mask.feather.evaluate(time_code) as f32,
+            expansion: mask.expansion.evaluate(time_code) as f32,
+            shape: match mask.shape {
+                MaskShape::Rectangle => 0,
+                MaskShape::Ellipse => 1,
+            },
+            mode: match mask.mode {
+                MaskMode::Add => 0,
+                MaskMode::Subtract => 1,
+                MaskMode::Intersect => 2,
+            },
+            inverted: mask.inverted as u32,
+        };
+
+        let uniform_buffer = wgpu
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Mask Uniform Buffer"),
+                contents: bytemuck::cast_slice(&[uniform]),
+                usage: wgpu::BufferUsages::UNIFORM,
+            });
+
+        let bind_group = wgpu.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Mask Bind Group"),
+            layout: &self.mask_bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(previous),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&self.sampler),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: uniform_buffer.as_entire_binding(),
+                },
+            ],
+        });
+
+        full_screen_pass(
+            encoder,
+            "Mask Render Pass",
+            &self.mask_pipeline,
+            &[&bind_group, &wgpu.global_bind_group],
+            target,
+        );
+    }
+}
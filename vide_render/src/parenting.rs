@@ -0,0 +1,202 @@
+use std::collections::{HashMap, HashSet};
+
+use euler::Mat4;
+use vide_common::{time_code::UnboundedTimecodeRange, FrameInfo};
+use vide_project::{
+    clip::{Clip, ClipId},
+    Project,
+};
+
+struct TransformEntry {
+    local: Mat4,
+    parent: Option<ClipId>,
+    transform_parent: Option<ClipId>,
+}
+
+/// Resolves the transforms of clips that have a transform parent for a single frame
+struct Resolver<'a> {
+    entries: &'a HashMap<ClipId, TransformEntry>,
+    root_transform: Mat4,
+    resolved: HashMap<ClipId, Mat4>,
+    visiting: HashSet<ClipId>,
+    /// Clips whose transform parent was already reported as broken
+    warned: &'a mut HashSet<ClipId>,
+}
+
+impl Resolver<'_> {
+    /// The absolute transform of a clip, `None` if it depends on itself
+    fn resolve(&mut self, id: ClipId) -> Option<Mat4> {
+        if let Some(transform) = self.resolved.get(&id) {
+            return Some(*transform);
+        }
+
+        let entry = self.entries.get(&id)?;
+
+        if !self.visiting.insert(id) {
+            return None;
+        }
+
+        let parented = entry
+            .transform_parent
+            .and_then(|parent| self.resolve(parent));
+
+        if entry.transform_parent.is_some() && parented.is_none() && self.warned.insert(id) {
+            log::warn!("Ignoring transform parent of clip {id:?}, it is missing or a cycle");
+        }
+
+        let base = match (parented, entry.parent) {
+            (Some(transform), _) => Some(transform),
+            (None, Some(parent)) => self.resolve(parent),
+            (None, None) => Some(self.root_transform),
+        };
+
+        self.visiting.remove(&id);
+
+        let transform = base? * entry.local;
+        self.resolved.insert(id, transform);
+
+        Some(transform)
+    }
+}
+
+fn collect_entries(
+    clip: &Clip,
+    frame_info: FrameInfo,
+    parent_range: UnboundedTimecodeRange,
+    parent: Option<ClipId>,
+    entries: &mut HashMap<ClipId, TransformEntry>,
+) {
+    let absolute_range = clip.range().make_absolute(parent_range);
+    let local_frame_info = frame_info.make_local(absolute_range);
+
+    entries.insert(
+        clip.id(),
+        TransformEntry {
+            local: clip.transform_at(&local_frame_info),
+            parent,
+            transform_parent: clip.transform_parent(),
+        },
+    );
+
+    let content_frame_info = FrameInfo {
+        time_code: absolute_range.start().unwrap_or_default()
+            + clip.content_time(local_frame_info.time_code),
+        ..frame_info
+    };
+
+    let content_range = clip.content_range(absolute_range);
+
+    for child in clip.children() {
+        collect_entries(
+            child,
+            content_frame_info,
+            content_range,
+            Some(clip.id()),
+            entries,
+        );
+    }
+
+    // Mattes are rendered in the space of the clip they cut out
+    if let Some(track_matte) = clip.track_matte() {
+        collect_entries(
+            &track_matte.source,
+            frame_info,
+            parent_range,
+            parent,
+            entries,
+        );
+    }
+}
+
+/// Evaluate the absolute transforms of all clips with a transform parent at `frame_info`.
+/// Cycles are broken at the same clip every frame, going through clips by id. Broken
+/// parents are only logged the first time they end up in `warned`.
+pub(crate) fn resolve_parented_transforms(
+    project: &Project,
+    frame_info: FrameInfo,
+    project_range: UnboundedTimecodeRange,
+    root_transform: Mat4,
+    warned: &mut HashSet<ClipId>,
+) -> HashMap<ClipId, Mat4> {
+    let mut entries = HashMap::new();
+
+    for clip in project.clips() {
+        collect_entries(clip, frame_info, project_range, None, &mut entries);
+    }
+
+    let mut resolver = Resolver {
+        entries: &entries,
+        root_transform,
+        resolved: HashMap::new(),
+        visiting: HashSet::new(),
+        warned,
+    };
+
+    let mut parented = entries
+        .iter()
+        .filter(|(_, entry)| entry.transform_parent.is_some())
+        .map(|(id, _)| *id)
+        .collect::<Vec<_>>();
+    parented.sort();
+
+    parented
+        .into_iter()
+        .filter_map(|id| Some((id, resolver.resolve(id)?)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use euler::vec2;
+    use vide_animate::AnimatedProperty;
+    use vide_common::prelude::TimeCode;
+
+    use super::*;
+
+    const FRAME_INFO: FrameInfo = FrameInfo {
+        time_code: TimeCode::new(0),
+        progress: 0.0,
+        resolution: (1920, 1080),
+    };
+
+    fn resolve(project: &Project, warned: &mut HashSet<ClipId>) -> HashMap<ClipId, Mat4> {
+        resolve_parented_transforms(
+            project,
+            FRAME_INFO,
+            UnboundedTimecodeRange::new(None, None),
+            Mat4::identity(),
+            warned,
+        )
+    }
+
+    #[test]
+    fn cycles_are_broken_deterministically() {
+        let mut first = Clip::new(0.0..1.0);
+        let mut second = Clip::new(0.0..1.0);
+        first.set_position(AnimatedProperty::with_default(vec2!(10.0, 0.0).into()));
+        second.set_position(AnimatedProperty::with_default(vec2!(0.0, 20.0).into()));
+        first.set_transform_parent(Some(second.id()));
+        second.set_transform_parent(Some(first.id()));
+
+        let (first_local, second_local) = (
+            first.transform_at(&FRAME_INFO),
+            second.transform_at(&FRAME_INFO),
+        );
+        let (first_id, second_id) = (first.id(), second.id());
+
+        let mut project = Project::new();
+        project.add_clip(first);
+        project.add_clip(second);
+
+        let mut warned = HashSet::new();
+
+        // The clip with the lowest id keeps its parent, every single time
+        for _ in 0..10 {
+            let transforms = resolve(&project, &mut warned);
+            assert_eq!(transforms[&first_id], second_local * first_local);
+            assert_eq!(transforms[&second_id], second_local);
+        }
+
+        assert_eq!(warned, HashSet::from([second_id]));
+    }
+}
@@ -0,0 +1,3 @@
+fn blend_color(backdrop: vec3<f32>, source: vec3<f32>) -> vec3<f32> {
+    return backdrop + source;
+}
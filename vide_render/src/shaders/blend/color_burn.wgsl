@@ -0,0 +1,6 @@
+fn blend_color(backdrop: vec3<f32>, source: vec3<f32>) -> vec3<f32> {
+    let burned = 1.0 - min(vec3<f32>(1.0), (1.0 - backdrop) / max(source, vec3<f32>(0.0001)));
+    let result = select(burned, vec3<f32>(0.0), source <= vec3<f32>(0.0));
+
+    return select(result, vec3<f32>(1.0), backdrop >= vec3<f32>(1.0));
+}
@@ -0,0 +1,6 @@
+fn blend_color(backdrop: vec3<f32>, source: vec3<f32>) -> vec3<f32> {
+    let dodged = min(vec3<f32>(1.0), backdrop / max(1.0 - source, vec3<f32>(0.0001)));
+    let result = select(dodged, vec3<f32>(1.0), source >= vec3<f32>(1.0));
+
+    return select(result, vec3<f32>(0.0), backdrop <= vec3<f32>(0.0));
+}
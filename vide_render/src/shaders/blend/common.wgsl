@@ -0,0 +1,79 @@
+struct BlendUniform {
+    opacity: f32,
+    premultiplied: u32,
+};
+
+@group(0) @binding(0) var a_texture: texture_2d<f32>;
+@group(0) @binding(1) var b_texture: texture_2d<f32>;
+@group(0) @binding(2) var source_sampler: sampler;
+@group(0) @binding(3) var<uniform> blend_uniform: BlendUniform;
+
+fn lum(color: vec3<f32>) -> f32 {
+    return dot(color, vec3<f32>(0.3, 0.59, 0.11));
+}
+
+fn clip_color(color: vec3<f32>) -> vec3<f32> {
+    let l = lum(color);
+    let n = min(color.r, min(color.g, color.b));
+    let x = max(color.r, max(color.g, color.b));
+
+    var result = color;
+
+    if (n < 0.0) {
+        result = l + (result - l) * l / (l - n);
+    }
+
+    if (x > 1.0) {
+        result = l + (result - l) * (1.0 - l) / (x - l);
+    }
+
+    return result;
+}
+
+fn set_lum(color: vec3<f32>, l: f32) -> vec3<f32> {
+    return clip_color(color + (l - lum(color)));
+}
+
+fn sat(color: vec3<f32>) -> f32 {
+    return max(color.r, max(color.g, color.b)) - min(color.r, min(color.g, color.b));
+}
+
+fn set_sat(color: vec3<f32>, s: f32) -> vec3<f32> {
+    let c_max = max(color.r, max(color.g, color.b));
+    let c_min = min(color.r, min(color.g, color.b));
+
+    if (c_max > c_min) {
+        return (color - c_min) * s / (c_max - c_min);
+    }
+
+    return vec3<f32>(0.0);
+}
+
+fn hard_light(backdrop: vec3<f32>, source: vec3<f32>) -> vec3<f32> {
+    let multiply = backdrop * source * 2.0;
+    let screen_source = source * 2.0 - 1.0;
+    let screen = backdrop + screen_source - backdrop * screen_source;
+
+    return select(screen, multiply, source <= vec3<f32>(0.5));
+}
+
+@fragment
+fn fragment_main(@location(0) tex_coords: vec2<f32>) -> @location(0) vec4<f32> {
+    var a = textureSample(a_texture, source_sampler, tex_coords);
+    let b = textureSample(b_texture, source_sampler, tex_coords);
+
+    if (blend_uniform.premultiplied != 0u) {
+        a = vec4<f32>(a.rgb / max(a.a, 0.0001), a.a);
+    }
+
+    a.a *= blend_uniform.opacity;
+
+    // Only blend where the backdrop is visible, use the source color as-is elsewhere
+    let mixed = (1.0 - b.a) * a.rgb + b.a * blend_color(b.rgb, a.rgb);
+
+    // A over B alpha blending
+    let alpha = a.a + b.a * (1.0 - a.a);
+    let color = (mixed * a.a + b.rgb * b.a * (1.0 - a.a)) / max(alpha, 0.0001);
+
+    return vec4<f32>(color, alpha);
+}
@@ -0,0 +1,12 @@
+fn blend_color(backdrop: vec3<f32>, source: vec3<f32>) -> vec3<f32> {
+    let d = select(
+        sqrt(backdrop),
+        ((16.0 * backdrop - 12.0) * backdrop + 4.0) * backdrop,
+        backdrop <= vec3<f32>(0.25),
+    );
+
+    let darkened = backdrop - (1.0 - 2.0 * source) * backdrop * (1.0 - backdrop);
+    let lightened = backdrop + (2.0 * source - 1.0) * (d - backdrop);
+
+    return select(lightened, darkened, source <= vec3<f32>(0.5));
+}
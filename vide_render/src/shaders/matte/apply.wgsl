@@ -0,0 +1,40 @@
+struct MatteUniform {
+    mode: u32,
+    opacity: f32,
+};
+
+@group(0) @binding(0) var source_texture: texture_2d<f32>;
+@group(0) @binding(1) var matte_texture: texture_2d<f32>;
+@group(0) @binding(2) var source_sampler: sampler;
+@group(0) @binding(3) var<uniform> matte_uniform: MatteUniform;
+
+const MODE_ALPHA: u32 = 0u;
+const MODE_INVERTED_ALPHA: u32 = 1u;
+const MODE_LUMA: u32 = 2u;
+const MODE_INVERTED_LUMA: u32 = 3u;
+
+@fragment
+fn fragment_main(@location(0) tex_coords: vec2<f32>) -> @location(0) vec4<f32> {
+    let source = textureSample(source_texture, source_sampler, tex_coords);
+    let matte = textureSample(matte_texture, source_sampler, tex_coords);
+
+    let alpha = matte.a * matte_uniform.opacity;
+    let luma = dot(matte.rgb, vec3<f32>(0.2126, 0.7152, 0.0722)) * alpha;
+
+    var coverage = alpha;
+
+    switch matte_uniform.mode {
+        case MODE_INVERTED_ALPHA: {
+            coverage = 1.0 - alpha;
+        }
+        case MODE_LUMA: {
+            coverage = luma;
+        }
+        case MODE_INVERTED_LUMA: {
+            coverage = 1.0 - luma;
+        }
+        default: {}
+    }
+
+    return vec4<f32>(source.rgb, source.a * clamp(coverage, 0.0, 1.0));
+}
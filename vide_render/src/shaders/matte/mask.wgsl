@@ -0,0 +1,98 @@
+struct MaskUniform {
+    inverse_transform: mat4x4<f32>,
+    center: vec2<f32>,
+    half_size: vec2<f32>,
+    _padding: vec2<f32>,
+    roundness: f32,
+    feather: f32,
+    expansion: f32,
+    shape: u32,
+    mode: u32,
+    inverted: u32,
+};
+
+@group(0) @binding(0) var previous_texture: texture_2d<f32>;
+@group(0) @binding(1) var source_sampler: sampler;
+@group(0) @binding(2) var<uniform> mask: MaskUniform;
+
+struct GlobalUniform {
+    ortho_matrix: mat4x4<f32>,
+};
+
+@group(1) @binding(0) var<uniform> global: GlobalUniform;
+
+const SHAPE_RECTANGLE: u32 = 0u;
+const SHAPE_ELLIPSE: u32 = 1u;
+
+const MODE_ADD: u32 = 0u;
+const MODE_SUBTRACT: u32 = 1u;
+const MODE_INTERSECT: u32 = 2u;
+
+fn rectangle_distance(position: vec2<f32>, half_size: vec2<f32>, radius: f32) -> f32 {
+    let r = min(radius, min(half_size.x, half_size.y));
+    let q = abs(position) - half_size + r;
+
+    return length(max(q, vec2<f32>(0.0))) + min(max(q.x, q.y), 0.0) - r;
+}
+
+fn ellipse_distance(position: vec2<f32>, half_size: vec2<f32>) -> f32 {
+    // Approximation, exact enough for anti-aliasing and feathering
+    let k0 = length(position / half_size);
+    let k1 = length(position / (half_size * half_size));
+
+    return k0 * (k0 - 1.0) / max(k1, 0.00001);
+}
+
+@fragment
+fn fragment_main(
+    @builtin(position) fragment_position: vec4<f32>,
+    @location(0) tex_coords: vec2<f32>,
+) -> @location(0) vec4<f32> {
+    let previous = textureSample(previous_texture, source_sampler, tex_coords).a;
+
+    // Fragment coordinates are pixels of the target from the top-left, transforms are
+    // in the space of the ortho matrix, which has a different resolution in precomps
+    let size = vec2<f32>(textureDimensions(previous_texture));
+    let ndc = vec2<f32>(1.0, -1.0) * (fragment_position.xy / size * 2.0 - 1.0);
+    let ortho = global.ortho_matrix;
+    let pixel = (ndc - ortho[3].xy) / vec2<f32>(ortho[0].x, ortho[1].y);
+    let position = (mask.inverse_transform * vec4<f32>(pixel, 0.0, 1.0)).xy - mask.center;
+
+    let half_size = max(abs(mask.half_size), vec2<f32>(0.00001));
+
+    var distance = 0.0;
+
+    switch mask.shape {
+        case SHAPE_ELLIPSE: {
+            distance = ellipse_distance(position, half_size);
+        }
+        default: {
+            distance = rectangle_distance(position, half_size, mask.roundness);
+        }
+    }
+
+    distance -= mask.expansion;
+
+    // The edge is always at least a pixel wide to anti-alias it
+    var coverage = clamp(0.5 - distance / max(mask.feather, 1.0), 0.0, 1.0);
+
+    if (mask.inverted != 0u) {
+        coverage = 1.0 - coverage;
+    }
+
+    var result = coverage;
+
+    switch mask.mode {
+        case MODE_SUBTRACT: {
+            result = previous * (1.0 - coverage);
+        }
+        case MODE_INTERSECT: {
+            result = min(previous, coverage);
+        }
+        default: {
+            result = max(previous, coverage);
+        }
+    }
+
+    return vec4<f32>(1.0, 1.0, 1.0, result);
+}
@@ -0,0 +1,28 @@
+struct MixUniform {
+    opacity: f32,
+};
+
+@group(0) @binding(0) var from_texture: texture_2d<f32>;
+@group(0) @binding(1) var to_texture: texture_2d<f32>;
+@group(0) @binding(2) var coverage_texture: texture_2d<f32>;
+@group(0) @binding(3) var source_sampler: sampler;
+@group(0) @binding(4) var<uniform> mix_uniform: MixUniform;
+
+@fragment
+fn fragment_main(@location(0) tex_coords: vec2<f32>) -> @location(0) vec4<f32> {
+    let from_color = textureSample(from_texture, source_sampler, tex_coords);
+    let to_color = textureSample(to_texture, source_sampler, tex_coords);
+    let coverage = textureSample(coverage_texture, source_sampler, tex_coords).a;
+
+    let amount = clamp(coverage * mix_uniform.opacity, 0.0, 1.0);
+
+    // Mix premultiplied, so transparent pixels don't bleed their color
+    let alpha = mix(from_color.a, to_color.a, amount);
+    let color = mix(from_color.rgb * from_color.a, to_color.rgb * to_color.a, amount);
+
+    if (alpha <= 0.0) {
+        return vec4<f32>(0.0);
+    }
+
+    return vec4<f32>(color / alpha, alpha);
+}
@@ -0,0 +1,54 @@
+const KIND_CROSSFADE: u32 = 0u;
+const KIND_DIP_TO_COLOR: u32 = 1u;
+const KIND_WIPE: u32 = 2u;
+const KIND_SLIDE: u32 = 3u;
+const KIND_PUSH: u32 = 4u;
+const KIND_ZOOM: u32 = 5u;
+
+fn zoomed(uv: vec2<f32>, scale: f32) -> vec2<f32> {
+    return (uv - 0.5) / scale + 0.5;
+}
+
+fn transition(uv: vec2<f32>, progress: f32) -> vec4<f32> {
+    let direction = transition_uniform.direction;
+
+    switch transition_uniform.kind {
+        case KIND_DIP_TO_COLOR: {
+            let color = transition_uniform.color;
+
+            if (progress < 0.5) {
+                return mix_colors(sample_from(uv), color, progress * 2.0);
+            }
+
+            return mix_colors(color, sample_to(uv), progress * 2.0 - 1.0);
+        }
+        case KIND_WIPE: {
+            let softness = transition_uniform.softness;
+            // 0 at the edge the wipe starts from, 1 at the edge it ends at
+            let distance = dot(uv - 0.5, direction) + 0.5;
+            let edge = progress * (1.0 + softness);
+            let coverage = clamp((edge - distance) / max(softness, 0.0001), 0.0, 1.0);
+
+            return mix_colors(sample_from(uv), sample_to(uv), coverage);
+        }
+        case KIND_SLIDE: {
+            return over(sample_to(uv + direction * (1.0 - progress)), sample_from(uv));
+        }
+        case KIND_PUSH: {
+            return over(
+                sample_to(uv + direction * (1.0 - progress)),
+                sample_from(uv - direction * progress),
+            );
+        }
+        case KIND_ZOOM: {
+            return mix_colors(
+                sample_from(zoomed(uv, 1.0 + progress)),
+                sample_to(zoomed(uv, 0.5 + progress * 0.5)),
+                progress,
+            );
+        }
+        default: {
+            return mix_colors(sample_from(uv), sample_to(uv), progress);
+        }
+    }
+}
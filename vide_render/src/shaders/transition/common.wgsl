@@ -0,0 +1,43 @@
+struct TransitionUniform {
+    color: vec4<f32>,
+    direction: vec2<f32>,
+    progress: f32,
+    softness: f32,
+    kind: u32,
+    from_opacity: f32,
+    to_opacity: f32,
+};
+
+@group(0) @binding(0) var from_texture: texture_2d<f32>;
+@group(0) @binding(1) var to_texture: texture_2d<f32>;
+@group(0) @binding(2) var source_sampler: sampler;
+@group(0) @binding(3) var<uniform> transition_uniform: TransitionUniform;
+
+fn sample_from(uv: vec2<f32>) -> vec4<f32> {
+    let color = textureSample(from_texture, source_sampler, uv);
+    return vec4<f32>(color.rgb, color.a * transition_uniform.from_opacity);
+}
+
+fn sample_to(uv: vec2<f32>) -> vec4<f32> {
+    let color = textureSample(to_texture, source_sampler, uv);
+    return vec4<f32>(color.rgb, color.a * transition_uniform.to_opacity);
+}
+
+// Mix two colors with straight alpha without bleeding the color of transparent pixels
+fn mix_colors(a: vec4<f32>, b: vec4<f32>, t: f32) -> vec4<f32> {
+    let alpha = mix(a.a, b.a, t);
+    let color = mix(a.rgb * a.a, b.rgb * b.a, t) / max(alpha, 0.0001);
+    return vec4<f32>(color, alpha);
+}
+
+// Draw `top` over `bottom`, both with straight alpha
+fn over(top: vec4<f32>, bottom: vec4<f32>) -> vec4<f32> {
+    let alpha = top.a + bottom.a * (1.0 - top.a);
+    let color = (top.rgb * top.a + bottom.rgb * bottom.a * (1.0 - top.a)) / max(alpha, 0.0001);
+    return vec4<f32>(color, alpha);
+}
+
+@fragment
+fn fragment_main(@location(0) tex_coords: vec2<f32>) -> @location(0) vec4<f32> {
+    return transition(tex_coords, transition_uniform.progress);
+}
@@ -14,6 +14,29 @@ impl FactoryTexture {
     pub fn view(&self) -> &wgpu::TextureView {
         &self.view
     }
+
+    /// Factory textures keep their contents when returned, clear them before
+    /// reading from them
+    pub fn clear(&self, encoder: &mut wgpu::CommandEncoder) {
+        self.fill(encoder, wgpu::Color::TRANSPARENT);
+    }
+
+    pub fn fill(&self, encoder: &mut wgpu::CommandEncoder, color: wgpu::Color) {
+        encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Fill Factory Texture Render Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &self.view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(color),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+    }
 }
 
 #[derive(Debug)]
@@ -0,0 +1,231 @@
+use std::collections::HashMap;
+
+use pollster::FutureExt;
+use vide_common::render::Wgpu;
+use vide_project::transition::{Direction, TransitionKind};
+use wgpu::util::DeviceExt;
+
+use crate::matte::{
+    create_pipeline, full_screen_pass, sampler_entry, texture_entry, uniform_entry,
+};
+
+const TRANSITION_COMMON_SOURCE: &str = include_str!("./shaders/transition/common.wgsl");
+
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy)]
+struct TransitionUniform {
+    color: [f32; 4],
+    direction: [f32; 2],
+    progress: f32,
+    softness: f32,
+    kind: u32,
+    from_opacity: f32,
+    to_opacity: f32,
+    _padding: f32,
+}
+
+unsafe impl bytemuck::Pod for TransitionUniform {}
+unsafe impl bytemuck::Zeroable for TransitionUniform {}
+
+/// The direction in texture coordinates, where y points down
+fn direction_vector(direction: Direction) -> [f32; 2] {
+    match direction {
+        Direction::Left => [-1.0, 0.0],
+        Direction::Right => [1.0, 0.0],
+        Direction::Up => [0.0, -1.0],
+        Direction::Down => [0.0, 1.0],
+    }
+}
+
+/// Pipelines that combine two clips into one during a transition
+#[derive(Debug)]
+pub struct Transitions {
+    target: wgpu::TextureFormat,
+    sampler: wgpu::Sampler,
+    bind_group_layout: wgpu::BindGroupLayout,
+    builtin_pipeline: wgpu::RenderPipeline,
+    /// Pipelines for [`TransitionKind::Custom`], by shader source. `None` if the
+    /// shader failed to compile.
+    custom_pipelines: HashMap<String, Option<wgpu::RenderPipeline>>,
+}
+
+impl Transitions {
+    pub fn load(wgpu: &Wgpu, target: wgpu::TextureFormat) -> Self {
+        log::info!("Loading transitions for {target:?}");
+
+        let sampler = wgpu.device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Transition Sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToBorder,
+            address_mode_v: wgpu::AddressMode::ClampToBorder,
+            address_mode_w: wgpu::AddressMode::ClampToBorder,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Linear,
+            lod_min_clamp: 0.0,
+            lod_max_clamp: 32.0,
+            compare: None,
+            anisotropy_clamp: 1,
+            border_color: Some(wgpu::SamplerBorderColor::TransparentBlack),
+        });
+
+        let bind_group_layout =
+            wgpu.device
+                .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                    label: Some("Transition Bind Group Layout"),
+                    entries: &[
+                        texture_entry(0),
+                        texture_entry(1),
+                        sampler_entry(2),
+                        uniform_entry(3),
+                    ],
+                });
+
+        let builtin_pipeline = create_pipeline(
+            wgpu,
+            "Transition Pipeline",
+            target,
+            &[&bind_group_layout],
+            &format!(
+                "{TRANSITION_COMMON_SOURCE}\n{}",
+                include_str!("./shaders/transition/builtin.wgsl")
+            ),
+        );
+
+        Self {
+            target,
+            sampler,
+            bind_group_layout,
+            builtin_pipeline,
+            custom_pipelines: HashMap::new(),
+        }
+    }
+
+    /// Compile the shader of a [`TransitionKind::Custom`] ahead of rendering, returns
+    /// `false` if it doesn't compile. Failing transitions are rendered as a crossfade.
+    pub fn compile(&mut self, wgpu: &Wgpu, source: &str) -> bool {
+        if let Some(pipeline) = self.custom_pipelines.get(source) {
+            return pipeline.is_some();
+        }
+
+        log::info!("Compiling custom transition");
+
+        wgpu.device.push_error_scope(wgpu::ErrorFilter::Validation);
+
+        let pipeline = create_pipeline(
+            wgpu,
+            "Custom Transition Pipeline",
+            self.target,
+            &[&self.bind_group_layout],
+            &format!("{TRANSITION_COMMON_SOURCE}\n{source}"),
+        );
+
+        let pipeline = match wgpu.device.pop_error_scope().block_on() {
+            Some(error) => {
+                log::error!("Custom transition failed to compile, using a crossfade: {error}");
+                None
+            }
+            None => Some(pipeline),
+        };
+
+        let compiled = pipeline.is_some();
+        self.custom_pipelines.insert(source.to_string(), pipeline);
+        compiled
+    }
+
+    /// Combine `from` and `to` using `kind` and write the result to `target`
+    #[allow(clippy::too_many_arguments)]
+    pub fn apply(
+        &mut self,
+        wgpu: &Wgpu,
+        encoder: &mut wgpu::CommandEncoder,
+        kind: &TransitionKind,
+        progress: f32,
+        from: &wgpu::TextureView,
+        from_opacity: f32,
+        to: &wgpu::TextureView,
+        to_opacity: f32,
+        target: &wgpu::TextureView,
+    ) {
+        let mut uniform = TransitionUniform {
+            progress,
+            from_opacity,
+            to_opacity,
+            ..Default::default()
+        };
+
+        match kind {
+            TransitionKind::Crossfade | TransitionKind::Custom(_) => uniform.kind = 0,
+            TransitionKind::DipToColor(color) => {
+                uniform.kind = 1;
+                uniform.color = (*color).into();
+            }
+            TransitionKind::Wipe {
+                direction,
+                softness,
+            } => {
+                uniform.kind = 2;
+                uniform.direction = direction_vector(*direction);
+                uniform.softness = *softness as f32;
+            }
+            TransitionKind::Slide(direction) => {
+                uniform.kind = 3;
+                uniform.direction = direction_vector(*direction);
+            }
+            TransitionKind::Push(direction) => {
+                uniform.kind = 4;
+                uniform.direction = direction_vector(*direction);
+            }
+            TransitionKind::Zoom => uniform.kind = 5,
+        }
+
+        // Custom transitions that failed to compile fall back to a crossfade
+        let pipeline = match kind {
+            TransitionKind::Custom(source) => {
+                self.compile(wgpu, source);
+                self.custom_pipelines[source]
+                    .as_ref()
+                    .unwrap_or(&self.builtin_pipeline)
+            }
+            _ => &self.builtin_pipeline,
+        };
+
+        let uniform_buffer = wgpu
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Transition Uniform Buffer"),
+                contents: bytemuck::cast_slice(&[uniform]),
+                usage: wgpu::BufferUsages::UNIFORM,
+            });
+
+        let bind_group = wgpu.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Transition Bind Group"),
+            layout: &self.bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(from),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(to),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::Sampler(&self.sampler),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: uniform_buffer.as_entire_binding(),
+                },
+            ],
+        });
+
+        full_screen_pass(
+            encoder,
+            "Transition Render Pass",
+            pipeline,
+            &[&bind_group],
+            target,
+        );
+    }
+}
@@ -0,0 +1,92 @@
+use serde::{Deserialize, Serialize};
+use vide_animate::AnimatedProperty;
+use vide_common::{
+    config::RenderConfiguration, effect::Effect, render::Wgpu, standards::FRAGMENT_COLOR_TARGET,
+    FrameInfo,
+};
+
+use crate::effect_pass::EffectPass;
+
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy)]
+struct ColorCorrectionData {
+    exposure: f32,
+    brightness: f32,
+    contrast: f32,
+    saturation: f32,
+    gamma: f32,
+    _padding: [f32; 3],
+}
+
+unsafe impl bytemuck::Pod for ColorCorrectionData {}
+unsafe impl bytemuck::Zeroable for ColorCorrectionData {}
+
+/// Basic color grading, the defaults leave the image unchanged
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ColorCorrection {
+    /// In stops, `1.0` doubles the brightness
+    pub exposure: AnimatedProperty<f64>,
+    /// Added to every channel
+    pub brightness: AnimatedProperty<f64>,
+    pub contrast: AnimatedProperty<f64>,
+    /// `0.0` is grayscale
+    pub saturation: AnimatedProperty<f64>,
+    pub gamma: AnimatedProperty<f64>,
+}
+
+impl Default for ColorCorrection {
+    fn default() -> Self {
+        Self {
+            exposure: AnimatedProperty::with_default(0.0),
+            brightness: AnimatedProperty::with_default(0.0),
+            contrast: AnimatedProperty::with_default(1.0),
+            saturation: AnimatedProperty::with_default(1.0),
+            gamma: AnimatedProperty::with_default(1.0),
+        }
+    }
+}
+
+impl Effect for ColorCorrection {
+    fn init(&mut self, wgpu: &Wgpu, _config: &RenderConfiguration) {
+        wgpu.resources
+            .get_or_init::<Self, _>(FRAGMENT_COLOR_TARGET, || {
+                EffectPass::new(
+                    wgpu,
+                    "ColorCorrection Effect",
+                    include_str!("./shaders/color_correction.wgsl"),
+                )
+            });
+    }
+
+    fn apply(
+        &mut self,
+        wgpu: &Wgpu,
+        _frame_info: &FrameInfo,
+        local_frame_info: &FrameInfo,
+        encoder: &mut wgpu::CommandEncoder,
+        source: &wgpu::TextureView,
+        destination: &wgpu::TextureView,
+    ) {
+        let time_code = local_frame_info.time_code;
+
+        let data = ColorCorrectionData {
+            exposure: self.exposure.evaluate(time_code) as f32,
+            brightness: self.brightness.evaluate(time_code) as f32,
+            contrast: self.contrast.evaluate(time_code) as f32,
+            saturation: self.saturation.evaluate(time_code) as f32,
+            gamma: self.gamma.evaluate(time_code) as f32,
+            ..Default::default()
+        };
+
+        wgpu.resources
+            .get::<Self, EffectPass>(FRAGMENT_COLOR_TARGET)
+            .expect("init() should be called before apply()")
+            .apply(
+                wgpu,
+                encoder,
+                bytemuck::cast_slice(&[data]),
+                source,
+                destination,
+            );
+    }
+}
@@ -0,0 +1,480 @@
+use std::{fmt::Debug, sync::Arc};
+
+use euler::{vec2, vec3, Mat4, Quat, Trs, Vec2};
+use serde::{Deserialize, Serialize};
+use vide_animate::AnimatedProperty;
+use vide_common::{
+    color::Color,
+    config::RenderConfiguration,
+    layout::{Position, Size},
+    prelude::TimeCode,
+    render::Wgpu,
+    standards::FRAGMENT_COLOR_TARGET,
+    visible_object::VisibleObject,
+    FrameInfo,
+};
+use wgpu::util::DeviceExt;
+
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy)]
+struct InstanceData {
+    matrix: [[f32; 4]; 4],
+    color: [f32; 4],
+    shape: u32,
+    _padding: [u32; 3],
+}
+
+impl InstanceData {
+    const ATTRIBS: [wgpu::VertexAttribute; 6] = wgpu::vertex_attr_array![
+        2 => Float32x4,
+        3 => Float32x4,
+        4 => Float32x4,
+        5 => Float32x4,
+        6 => Float32x4,
+        7 => Uint32,
+    ];
+
+    const fn desc<'a>() -> wgpu::VertexBufferLayout<'a> {
+        wgpu::VertexBufferLayout {
+            array_stride: size_of::<InstanceData>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &Self::ATTRIBS,
+        }
+    }
+}
+
+unsafe impl bytemuck::Pod for InstanceData {}
+unsafe impl bytemuck::Zeroable for InstanceData {}
+
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy)]
+struct Vertex {
+    position: [f32; 2],
+    uv: [f32; 2],
+}
+
+impl Vertex {
+    const ATTRIBS: [wgpu::VertexAttribute; 2] =
+        wgpu::vertex_attr_array![0 => Float32x2, 1 => Float32x2];
+
+    const fn desc<'a>() -> wgpu::VertexBufferLayout<'a> {
+        wgpu::VertexBufferLayout {
+            array_stride: size_of::<Vertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &Self::ATTRIBS,
+        }
+    }
+}
+
+unsafe impl bytemuck::Pod for Vertex {}
+unsafe impl bytemuck::Zeroable for Vertex {}
+
+/// The pipeline shared by all instanced shapes, it outputs premultiplied alpha so
+/// overlapping instances blend correctly
+#[derive(Debug)]
+pub struct InstancedShapeRenderer {
+    vertex_buffer: wgpu::Buffer,
+    pipeline: wgpu::RenderPipeline,
+}
+
+impl InstancedShapeRenderer {
+    pub fn new(wgpu: &Wgpu, _config: &RenderConfiguration) -> Self {
+        let shader_module = wgpu
+            .device
+            .create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some("InstancedShape Renderer Shader"),
+                source: wgpu::ShaderSource::Wgsl(
+                    include_str!("./shaders/instanced_shape.wgsl").into(),
+                ),
+            });
+
+        let vertex_buffer = wgpu
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("InstancedShape Renderer Vertex Buffer"),
+                contents: bytemuck::cast_slice(&[
+                    // Face 1
+                    Vertex {
+                        position: [-0.5, -0.5],
+                        uv: [0.0, 1.0],
+                    },
+                    Vertex {
+                        position: [0.5, -0.5],
+                        uv: [1.0, 1.0],
+                    },
+                    Vertex {
+                        position: [-0.5, 0.5],
+                        uv: [0.0, 0.0],
+                    },
+                    // Face 2
+                    Vertex {
+                        position: [-0.5, 0.5],
+                        uv: [0.0, 0.0],
+                    },
+                    Vertex {
+                        position: [0.5, -0.5],
+                        uv: [1.0, 1.0],
+                    },
+                    Vertex {
+                        position: [0.5, 0.5],
+                        uv: [1.0, 0.0],
+                    },
+                ]),
+                usage: wgpu::BufferUsages::VERTEX,
+            });
+
+        let pipeline_layout = wgpu
+            .device
+            .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("InstancedShape Renderer Pipeline Layout"),
+                bind_group_layouts: &[&wgpu.global_bind_group_layout],
+                push_constant_ranges: &[],
+            });
+
+        let pipeline = wgpu
+            .device
+            .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some("InstancedShape Renderer Pipeline"),
+                layout: Some(&pipeline_layout),
+                vertex: wgpu::VertexState {
+                    module: &shader_module,
+                    entry_point: None,
+                    compilation_options: Default::default(),
+                    buffers: &[Vertex::desc(), InstanceData::desc()],
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader_module,
+                    entry_point: None,
+                    compilation_options: Default::default(),
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format: FRAGMENT_COLOR_TARGET,
+                        blend: Some(wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING),
+                        write_mask: wgpu::ColorWrites::all(),
+                    })],
+                }),
+                primitive: wgpu::PrimitiveState {
+                    topology: wgpu::PrimitiveTopology::TriangleList,
+                    strip_index_format: None,
+                    front_face: wgpu::FrontFace::Ccw,
+                    cull_mode: Some(wgpu::Face::Back),
+                    unclipped_depth: false,
+                    polygon_mode: wgpu::PolygonMode::Fill,
+                    conservative: false,
+                },
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState {
+                    count: 1,
+                    mask: !0,
+                    alpha_to_coverage_enabled: false,
+                },
+                multiview: None,
+                cache: None,
+            });
+
+        Self {
+            vertex_buffer,
+            pipeline,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum InstanceShape {
+    #[default]
+    Rect,
+    Ellipse,
+}
+
+/// A single instance at one point in time, in pixels like the properties of a
+/// [`RectShape`](crate::rect_shape::RectShape)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Instance {
+    pub position: Vec2,
+    pub size: Vec2,
+    /// Rotation in degrees, clockwise
+    pub rotation: f64,
+    pub color: Color,
+}
+
+impl Default for Instance {
+    fn default() -> Self {
+        Self {
+            position: vec2!(0.0),
+            size: vec2!(1.0),
+            rotation: 0.0,
+            color: Color::WHITE,
+        }
+    }
+}
+
+/// The animated properties of an instance
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct InstanceProperties {
+    pub position: AnimatedProperty<Position>,
+    pub rotation: AnimatedProperty<f64>,
+    pub size: AnimatedProperty<Size>,
+    pub color: AnimatedProperty<Color>,
+}
+
+impl InstanceProperties {
+    fn evaluate(&self, frame_info: &FrameInfo) -> Instance {
+        let time_code = frame_info.time_code;
+
+        Instance {
+            position: self
+                .position
+                .evaluate(time_code)
+                .resolve(frame_info.resolution),
+            size: self.size.evaluate(time_code).resolve(frame_info.resolution),
+            rotation: self.rotation.evaluate(time_code),
+            color: self.color.evaluate(time_code),
+        }
+    }
+
+    fn last_keyframe_time(&self) -> Option<TimeCode> {
+        [
+            self.position.last_keyframe_time(),
+            self.rotation.last_keyframe_time(),
+            self.size.last_keyframe_time(),
+            self.color.last_keyframe_time(),
+        ]
+        .into_iter()
+        .flatten()
+        .max()
+    }
+}
+
+type GenerateInstance = dyn Fn(u32, &FrameInfo) -> Instance + Send + Sync;
+
+/// Computes the instances every frame, see [`InstancedShape::generated`]
+#[derive(Clone)]
+pub struct InstanceGenerator {
+    count: u32,
+    generate: Arc<GenerateInstance>,
+}
+
+impl Debug for InstanceGenerator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("InstanceGenerator")
+            .field("count", &self.count)
+            .finish_non_exhaustive()
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct InstancedShapeInternalData {
+    transform: Option<Mat4>,
+    buffer: Option<wgpu::Buffer>,
+    capacity: usize,
+    count: u32,
+}
+
+impl Clone for InstancedShapeInternalData {
+    fn clone(&self) -> Self {
+        Self {
+            transform: self.transform,
+            buffer: None,
+            capacity: 0,
+            count: 0,
+        }
+    }
+}
+
+/// Many copies of a shape drawn with a single draw call, for particles, dot grids and
+/// the like. The instances either have their own animated properties, or are computed
+/// every frame by a closure.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct InstancedShape {
+    pub shape: InstanceShape,
+    pub instances: Vec<InstanceProperties>,
+    /// Replaces `instances` if set, closures can't be saved with the project
+    #[serde(skip)]
+    pub generator: Option<InstanceGenerator>,
+    #[serde(skip)]
+    pub internal: InstancedShapeInternalData,
+}
+
+impl InstancedShape {
+    pub fn new(shape: InstanceShape, instances: Vec<InstanceProperties>) -> Self {
+        Self {
+            shape,
+            instances,
+            ..Default::default()
+        }
+    }
+
+    /// `count` instances computed by `generate` from their index and the local frame
+    /// info, every frame
+    pub fn generated(
+        shape: InstanceShape,
+        count: u32,
+        generate: impl Fn(u32, &FrameInfo) -> Instance + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            shape,
+            generator: Some(InstanceGenerator {
+                count,
+                generate: Arc::new(generate),
+            }),
+            ..Default::default()
+        }
+    }
+
+    pub fn instance_count(&self) -> u32 {
+        match &self.generator {
+            Some(generator) => generator.count,
+            None => self.instances.len() as u32,
+        }
+    }
+
+    fn to_data(&self, instance: Instance, transform: Mat4) -> InstanceData {
+        let matrix = Trs::new(
+            vec3!(instance.position, 0.0),
+            Quat::axis_angle(vec3!(0.0, 0.0, -1.0), instance.rotation.to_radians() as f32),
+            vec3!(instance.size, 1.0),
+        )
+        .matrix();
+
+        InstanceData {
+            matrix: (transform * matrix).into(),
+            color: instance.color.into(),
+            shape: match self.shape {
+                InstanceShape::Rect => 0,
+                InstanceShape::Ellipse => 1,
+            },
+            ..Default::default()
+        }
+    }
+
+    fn record(&self, wgpu: &Wgpu, pass: &mut wgpu::RenderPass<'_>) {
+        let Some(buffer) = self.internal.buffer.as_ref() else {
+            return;
+        };
+
+        if self.internal.count == 0 {
+            return;
+        }
+
+        let renderer = wgpu
+            .resources
+            .get::<Self, InstancedShapeRenderer>(FRAGMENT_COLOR_TARGET)
+            .expect("init() should be called before render()");
+
+        pass.set_pipeline(&renderer.pipeline);
+        pass.set_vertex_buffer(0, renderer.vertex_buffer.slice(..));
+        pass.set_vertex_buffer(1, buffer.slice(..));
+        pass.set_bind_group(0, &wgpu.global_bind_group, &[]);
+        pass.draw(0..6, 0..self.internal.count);
+    }
+}
+
+impl VisibleObject for InstancedShape {
+    fn init(&mut self, wgpu: &Wgpu, config: &RenderConfiguration) {
+        wgpu.resources
+            .get_or_init::<Self, _>(FRAGMENT_COLOR_TARGET, || {
+                InstancedShapeRenderer::new(wgpu, config)
+            });
+    }
+
+    /// Until the last keyframe of any instance, generated instances have no duration
+    fn duration(&self) -> Option<TimeCode> {
+        if self.generator.is_some() {
+            return None;
+        }
+
+        self.instances
+            .iter()
+            .filter_map(InstanceProperties::last_keyframe_time)
+            .max()
+            .filter(|time_code| time_code.value() > 0)
+    }
+
+    fn set_transform(&mut self, transform: Mat4) {
+        self.internal.transform = Some(transform);
+    }
+
+    fn update(&mut self, wgpu: &Wgpu, _frame_info: &FrameInfo, local_frame_info: &FrameInfo) {
+        let transform = self.internal.transform.unwrap_or_else(Mat4::identity);
+
+        let data = match &self.generator {
+            Some(generator) => (0..generator.count)
+                .map(|index| self.to_data((generator.generate)(index, local_frame_info), transform))
+                .collect::<Vec<_>>(),
+            None => self
+                .instances
+                .iter()
+                .map(|instance| self.to_data(instance.evaluate(local_frame_info), transform))
+                .collect(),
+        };
+
+        self.internal.count = data.len() as u32;
+
+        if data.is_empty() {
+            return;
+        }
+
+        // Grow the buffer when there are more instances than before
+        if self.internal.buffer.is_none() || self.internal.capacity < data.len() {
+            self.internal.buffer = Some(wgpu.device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("InstancedShape Instance Buffer"),
+                size: (size_of::<InstanceData>() * data.len()) as wgpu::BufferAddress,
+                usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::VERTEX,
+                mapped_at_creation: false,
+            }));
+            self.internal.capacity = data.len();
+        }
+
+        let buffer = self
+            .internal
+            .buffer
+            .as_ref()
+            .expect("buffer was just created");
+
+        wgpu.queue
+            .write_buffer(buffer, 0, bytemuck::cast_slice(&data));
+    }
+
+    fn render(
+        &mut self,
+        wgpu: &Wgpu,
+        _frame_info: &FrameInfo,
+        _local_frame_info: &FrameInfo,
+        encoder: &mut wgpu::CommandEncoder,
+        destination: &wgpu::TextureView,
+    ) {
+        let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("InstancedShape Render Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: destination,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+
+        self.record(wgpu, &mut pass);
+    }
+
+    fn premultiplied(&self) -> bool {
+        true
+    }
+
+    fn batched(&self) -> bool {
+        true
+    }
+
+    fn draw(
+        &mut self,
+        wgpu: &Wgpu,
+        _frame_info: &FrameInfo,
+        _local_frame_info: &FrameInfo,
+        pass: &mut wgpu::RenderPass<'_>,
+    ) {
+        self.record(wgpu, pass);
+    }
+}
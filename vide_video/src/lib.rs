@@ -1 +1,65 @@
+pub mod color_correction;
+pub mod effect_pass;
+pub mod instanced_shape;
+pub mod precomp;
 pub mod rect_shape;
+pub mod vignette;
+
+use vide_common::{effect::register_effect, visible_object::register_visible_object};
+
+/// Register the visible objects and effects of this crate, so projects using them can
+/// be saved and loaded
+pub fn register_types() {
+    register_visible_object::<rect_shape::RectShape>("RectShape");
+    register_visible_object::<instanced_shape::InstancedShape>("InstancedShape");
+    register_visible_object::<precomp::Precomp>("Precomp");
+    register_effect::<color_correction::ColorCorrection>("ColorCorrection");
+    register_effect::<vignette::Vignette>("Vignette");
+}
+
+#[cfg(test)]
+mod tests {
+    use euler::vec2;
+    use vide_animate::{
+        cubic_bezier::consts::EASE_OUT_QUAD, value, AnimatedProperty, KeyframeTiming,
+    };
+    use vide_common::types::TimeUnit;
+    use vide_project::{clip::Clip, Project};
+
+    use super::*;
+    use crate::{color_correction::ColorCorrection, rect_shape::RectShape};
+
+    #[test]
+    fn project_round_trip() {
+        register_types();
+
+        let mut clip = Clip::new(0.0..2.0);
+        clip.attach_video(RectShape {
+            position: AnimatedProperty::builder()
+                .keyframe(KeyframeTiming::Abs(TimeUnit::Seconds(0.0)), vec2!(0.0))
+                .keyframe_ease(
+                    KeyframeTiming::Abs(TimeUnit::Seconds(1.0)),
+                    vec2!(100.0, 50.0),
+                    EASE_OUT_QUAD,
+                )
+                .build(),
+            color: value("#da0037"),
+            ..Default::default()
+        });
+        clip.add_effect(ColorCorrection {
+            saturation: value(0.5),
+            ..Default::default()
+        });
+
+        let mut project = Project::new();
+        project.add_clip(clip);
+
+        let json = project.to_json().unwrap();
+        let loaded = Project::from_json(&json).unwrap();
+
+        assert_eq!(loaded.to_json().unwrap(), json);
+        assert!(json.contains("\"RectShape\""));
+        assert!(json.contains("\"ColorCorrection\""));
+        assert!(json.contains("\"CubicBezier\""));
+    }
+}
@@ -0,0 +1,329 @@
+use euler::{vec3, Mat4, Quat, Trs};
+use serde::{Deserialize, Serialize};
+use vide_common::{
+    config::RenderConfiguration,
+    prelude::TimeCode,
+    render::Wgpu,
+    standards::FRAGMENT_COLOR_TARGET,
+    types::{Resolution, TimeUnit},
+    visible_object::VisibleObject,
+    FrameInfo,
+};
+use vide_project::Project;
+use vide_render::compositor::Compositor;
+use wgpu::util::DeviceExt;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+struct PrecompData {
+    matrix: [[f32; 4]; 4],
+}
+
+unsafe impl bytemuck::Pod for PrecompData {}
+unsafe impl bytemuck::Zeroable for PrecompData {}
+
+fn scale_matrix(x: f32, y: f32) -> Mat4 {
+    Trs::new(vec3!(0.0), Quat::identity(), vec3!(x, y, 1.0)).matrix()
+}
+
+/// Draws the composited frame of a precomp as a textured quad
+#[derive(Debug)]
+struct PrecompRenderer {
+    sampler: wgpu::Sampler,
+    bind_group_layout: wgpu::BindGroupLayout,
+    pipeline: wgpu::RenderPipeline,
+}
+
+impl PrecompRenderer {
+    fn new(wgpu: &Wgpu) -> Self {
+        let shader_module = wgpu
+            .device
+            .create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some("Precomp Renderer Shader"),
+                source: wgpu::ShaderSource::Wgsl(include_str!("./shaders/precomp.wgsl").into()),
+            });
+
+        let sampler = wgpu.device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Precomp Sampler"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        let bind_group_layout =
+            wgpu.device
+                .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                    label: Some("Precomp Renderer Bind Group Layout"),
+                    entries: &[
+                        wgpu::BindGroupLayoutEntry {
+                            binding: 0,
+                            visibility: wgpu::ShaderStages::VERTEX,
+                            ty: wgpu::BindingType::Buffer {
+                                ty: wgpu::BufferBindingType::Uniform,
+                                has_dynamic_offset: false,
+                                min_binding_size: None,
+                            },
+                            count: None,
+                        },
+                        wgpu::BindGroupLayoutEntry {
+                            binding: 1,
+                            visibility: wgpu::ShaderStages::FRAGMENT,
+                            ty: wgpu::BindingType::Texture {
+                                sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                                view_dimension: wgpu::TextureViewDimension::D2,
+                                multisampled: false,
+                            },
+                            count: None,
+                        },
+                        wgpu::BindGroupLayoutEntry {
+                            binding: 2,
+                            visibility: wgpu::ShaderStages::FRAGMENT,
+                            ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                            count: None,
+                        },
+                    ],
+                });
+
+        let pipeline_layout = wgpu
+            .device
+            .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Precomp Renderer Pipeline Layout"),
+                bind_group_layouts: &[&bind_group_layout, &wgpu.global_bind_group_layout],
+                push_constant_ranges: &[],
+            });
+
+        let pipeline = wgpu
+            .device
+            .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some("Precomp Renderer Pipeline"),
+                layout: Some(&pipeline_layout),
+                vertex: wgpu::VertexState {
+                    module: &shader_module,
+                    entry_point: None,
+                    compilation_options: Default::default(),
+                    buffers: &[],
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader_module,
+                    entry_point: None,
+                    compilation_options: Default::default(),
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format: FRAGMENT_COLOR_TARGET,
+                        blend: None,
+                        write_mask: wgpu::ColorWrites::all(),
+                    })],
+                }),
+                primitive: wgpu::PrimitiveState {
+                    topology: wgpu::PrimitiveTopology::TriangleList,
+                    strip_index_format: None,
+                    front_face: wgpu::FrontFace::Ccw,
+                    // Negative scales flip the quad
+                    cull_mode: None,
+                    unclipped_depth: false,
+                    polygon_mode: wgpu::PolygonMode::Fill,
+                    conservative: false,
+                },
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState {
+                    count: 1,
+                    mask: !0,
+                    alpha_to_coverage_enabled: false,
+                },
+                multiview: None,
+                cache: None,
+            });
+
+        Self {
+            sampler,
+            bind_group_layout,
+            pipeline,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct PrecompInternalData {
+    transform: Option<Mat4>,
+    /// The resolution of the render this precomp is part of
+    render_resolution: Resolution,
+    compositor: Option<Compositor>,
+}
+
+impl Clone for PrecompInternalData {
+    fn clone(&self) -> Self {
+        Self {
+            transform: self.transform,
+            render_resolution: self.render_resolution,
+            compositor: None,
+        }
+    }
+}
+
+/// A complete project used as the video of a clip, rendered at its own resolution and
+/// frame rate
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Precomp {
+    pub project: Project,
+    /// Only `resolution` and `frames_per_second` are used
+    pub config: RenderConfiguration,
+    /// Render the clips of the project directly in the parent, using the transform of
+    /// the clip, instead of rendering them at the project's resolution first. This keeps
+    /// scaled up precomps sharp, but content outside of the precomp isn't cut off.
+    pub collapse_transformations: bool,
+    #[serde(skip)]
+    pub internal: PrecompInternalData,
+}
+
+impl Precomp {
+    pub fn new(project: Project, config: RenderConfiguration) -> Self {
+        Self {
+            project,
+            config,
+            collapse_transformations: false,
+            internal: PrecompInternalData::default(),
+        }
+    }
+
+    pub fn collapsed(mut self) -> Self {
+        self.collapse_transformations = true;
+        self
+    }
+}
+
+impl VisibleObject for Precomp {
+    fn init(&mut self, wgpu: &Wgpu, config: &RenderConfiguration) {
+        wgpu.resources
+            .get_or_init::<Self, _>(FRAGMENT_COLOR_TARGET, || PrecompRenderer::new(wgpu));
+
+        self.project.infer_durations();
+        Compositor::init_project(wgpu, &mut self.project, config);
+
+        self.internal.render_resolution = config.resolution;
+        let mut compositor = Compositor::new(
+            wgpu,
+            match self.collapse_transformations {
+                true => config.resolution,
+                false => self.config.resolution,
+            },
+        );
+        compositor.prepare_transitions(wgpu, &self.project);
+        self.internal.compositor = Some(compositor);
+    }
+
+    fn duration(&self) -> Option<TimeCode> {
+        // The nested clips are only inferred in `init()`
+        Some(self.project.inferred_duration())
+    }
+
+    fn set_transform(&mut self, transform: Mat4) {
+        self.internal.transform = Some(transform);
+    }
+
+    fn update(&mut self, _wgpu: &Wgpu, _frame_info: &FrameInfo, _local_frame_info: &FrameInfo) {}
+
+    fn render(
+        &mut self,
+        wgpu: &Wgpu,
+        _frame_info: &FrameInfo,
+        local_frame_info: &FrameInfo,
+        encoder: &mut wgpu::CommandEncoder,
+        destination: &wgpu::TextureView,
+    ) {
+        let renderer = wgpu
+            .resources
+            .get::<Self, PrecompRenderer>(FRAGMENT_COLOR_TARGET)
+            .expect("init() should be called before render()");
+        let compositor = self
+            .internal
+            .compositor
+            .as_mut()
+            .expect("compositor should be set in init()");
+
+        let transform = self.internal.transform.unwrap_or_else(Mat4::identity);
+        let (render_width, render_height) = self.internal.render_resolution;
+        let (width, height) = self.config.resolution;
+
+        // Snap to the frame rate of the precomp
+        let fps = self.config.frames_per_second;
+        let time_code: TimeCode =
+            TimeUnit::Seconds(local_frame_info.time_code.frame(fps) as f64 / fps).into();
+
+        let frame_info = FrameInfo {
+            time_code,
+            progress: time_code.seconds() / self.project.duration().seconds(),
+            resolution: self.config.resolution,
+        };
+
+        // The global ortho matrix maps the render's resolution to the screen, the
+        // transforms make up for the different resolution of the texture or precomp
+        let (root_transform, matrix) = match self.collapse_transformations {
+            true => (
+                transform,
+                scale_matrix(render_width as f32, render_height as f32),
+            ),
+            false => (
+                scale_matrix(
+                    render_width as f32 / width as f32,
+                    render_height as f32 / height as f32,
+                ),
+                transform * scale_matrix(width as f32, height as f32),
+            ),
+        };
+
+        let texture =
+            compositor.render_frame(wgpu, encoder, &mut self.project, frame_info, root_transform);
+
+        let uniform_buffer = wgpu
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Precomp Uniform Buffer"),
+                contents: bytemuck::cast_slice(&[PrecompData {
+                    matrix: matrix.into(),
+                }]),
+                usage: wgpu::BufferUsages::UNIFORM,
+            });
+
+        let bind_group = wgpu.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Precomp Bind Group"),
+            layout: &renderer.bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: uniform_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(texture.view()),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::Sampler(&renderer.sampler),
+                },
+            ],
+        });
+
+        {
+            let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Precomp Render Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: destination,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+
+            pass.set_pipeline(&renderer.pipeline);
+            pass.set_bind_group(0, &bind_group, &[]);
+            pass.set_bind_group(1, &wgpu.global_bind_group, &[]);
+            pass.draw(0..6, 0..1);
+        }
+
+        compositor.return_texture(texture);
+    }
+}
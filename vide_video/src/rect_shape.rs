@@ -1,25 +1,17 @@
-use std::sync::{Once, OnceLock};
-
 use euler::{vec2, vec3, Mat4, Quat, Trs, Vec2};
+use serde::{Deserialize, Serialize};
 use vide_animate::AnimatedProperty;
 use vide_common::{
-    color::Color, config::RenderConfiguration, prelude::TimeCode, render::Wgpu,
-    standards::FRAGMENT_COLOR_TARGET, visible_object::VisibleObject,
+    color::Color,
+    config::RenderConfiguration,
+    layout::{Position, Size},
+    render::Wgpu,
+    standards::FRAGMENT_COLOR_TARGET,
+    visible_object::VisibleObject,
+    FrameInfo,
 };
 use wgpu::util::DeviceExt;
 
-static RENDERER: OnceLock<RectShapeRenderer> = OnceLock::new();
-
-fn init_renderer(wgpu: &Wgpu, config: &RenderConfiguration) {
-    static ONCE: Once = Once::new();
-
-    ONCE.call_once(move || RENDERER.set(RectShapeRenderer::new(wgpu, config)).unwrap());
-}
-
-fn renderer<'a>() -> &'a RectShapeRenderer {
-    unsafe { RENDERER.get().unwrap_unchecked() }
-}
-
 #[repr(C)]
 #[derive(Debug, Default, Clone, Copy)]
 struct RectShapeData {
@@ -58,6 +50,7 @@ pub struct RectShapeRenderer {
     bind_group_layout: wgpu::BindGroupLayout,
     vertex_buffer: wgpu::Buffer,
     pipeline: wgpu::RenderPipeline,
+    batch_pipeline: wgpu::RenderPipeline,
 }
 
 impl RectShapeRenderer {
@@ -128,50 +121,58 @@ impl RectShapeRenderer {
                 push_constant_ranges: &[],
             });
 
-        let pipeline = wgpu
-            .device
-            .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-                label: Some("Rect Shape Renderer Pipeline"),
-                layout: Some(&pipeline_layout),
-                vertex: wgpu::VertexState {
-                    module: &shader_module,
-                    entry_point: None,
-                    compilation_options: Default::default(),
-                    buffers: &[Vertex::desc()],
-                },
-                fragment: Some(wgpu::FragmentState {
-                    module: &shader_module,
-                    entry_point: None,
-                    compilation_options: Default::default(),
-                    targets: &[Some(wgpu::ColorTargetState {
-                        format: FRAGMENT_COLOR_TARGET,
-                        blend: None,
-                        write_mask: wgpu::ColorWrites::all(),
-                    })],
-                }),
-                primitive: wgpu::PrimitiveState {
-                    topology: wgpu::PrimitiveTopology::TriangleList,
-                    strip_index_format: None,
-                    front_face: wgpu::FrontFace::Ccw,
-                    cull_mode: Some(wgpu::Face::Back),
-                    unclipped_depth: false,
-                    polygon_mode: wgpu::PolygonMode::Fill,
-                    conservative: false,
-                },
-                depth_stencil: None,
-                multisample: wgpu::MultisampleState {
-                    count: 1,
-                    mask: !0,
-                    alpha_to_coverage_enabled: false,
-                },
-                multiview: None,
-                cache: None,
-            });
+        // Rendering on its own writes straight alpha, drawing in a batch blends
+        // premultiplied alpha over the other objects
+        let create_pipeline = |label, fragment_entry_point, blend| {
+            wgpu.device
+                .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                    label: Some(label),
+                    layout: Some(&pipeline_layout),
+                    vertex: wgpu::VertexState {
+                        module: &shader_module,
+                        entry_point: None,
+                        compilation_options: Default::default(),
+                        buffers: &[Vertex::desc()],
+                    },
+                    fragment: Some(wgpu::FragmentState {
+                        module: &shader_module,
+                        entry_point: Some(fragment_entry_point),
+                        compilation_options: Default::default(),
+                        targets: &[Some(wgpu::ColorTargetState {
+                            format: FRAGMENT_COLOR_TARGET,
+                            blend,
+                            write_mask: wgpu::ColorWrites::all(),
+                        })],
+                    }),
+                    primitive: wgpu::PrimitiveState {
+                        topology: wgpu::PrimitiveTopology::TriangleList,
+                        strip_index_format: None,
+                        front_face: wgpu::FrontFace::Ccw,
+                        cull_mode: Some(wgpu::Face::Back),
+                        unclipped_depth: false,
+                        polygon_mode: wgpu::PolygonMode::Fill,
+                        conservative: false,
+                    },
+                    depth_stencil: None,
+                    multisample: wgpu::MultisampleState {
+                        count: 1,
+                        mask: !0,
+                        alpha_to_coverage_enabled: false,
+                    },
+                    multiview: None,
+                    cache: None,
+                })
+        };
 
         Self {
+            pipeline: create_pipeline("Rect Shape Renderer Pipeline", "fs_main", None),
+            batch_pipeline: create_pipeline(
+                "Rect Shape Renderer Batch Pipeline",
+                "fs_premultiplied",
+                Some(wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING),
+            ),
             bind_group_layout,
             vertex_buffer,
-            pipeline,
         }
     }
 }
@@ -193,21 +194,28 @@ impl Clone for RectShapeInternalData {
     }
 }
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct RectShape {
-    pub position: AnimatedProperty<Vec2>,
+    pub position: AnimatedProperty<Position>,
     pub rotation: AnimatedProperty<f64>,
-    pub size: AnimatedProperty<Vec2>,
+    pub size: AnimatedProperty<Size>,
+    #[serde(with = "vide_animate::serialize::vec2")]
     pub pivot: AnimatedProperty<Vec2>,
     pub color: AnimatedProperty<Color>,
+    #[serde(skip)]
     pub internal: RectShapeInternalData,
 }
 
 impl RectShape {
-    fn to_data(&self, transform: euler::Mat4, time_code: TimeCode) -> RectShapeData {
-        let position = self.position.evaluate(time_code);
+    fn to_data(&self, transform: euler::Mat4, frame_info: &FrameInfo) -> RectShapeData {
+        let time_code = frame_info.time_code;
+
+        let position = self
+            .position
+            .evaluate(time_code)
+            .resolve(frame_info.resolution);
         let rotation = self.rotation.evaluate(time_code);
-        let size = self.size.evaluate(time_code);
+        let size = self.size.evaluate(time_code).resolve(frame_info.resolution);
         let pivot = self.pivot.evaluate(time_code);
         let color = self.color.evaluate(time_code);
 
@@ -230,7 +238,11 @@ impl RectShape {
 
 impl VisibleObject for RectShape {
     fn init(&mut self, wgpu: &Wgpu, config: &RenderConfiguration) {
-        init_renderer(wgpu, config);
+        let renderer = wgpu
+            .resources
+            .get_or_init::<Self, _>(FRAGMENT_COLOR_TARGET, || {
+                RectShapeRenderer::new(wgpu, config)
+            });
 
         let buffer = wgpu.device.create_buffer(&wgpu::BufferDescriptor {
             label: Some("RectShape Buffer"),
@@ -241,7 +253,7 @@ impl VisibleObject for RectShape {
 
         let bind_group = wgpu.device.create_bind_group(&wgpu::BindGroupDescriptor {
             label: Some("RectShape Bind Group"),
-            layout: &renderer().bind_group_layout,
+            layout: &renderer.bind_group_layout,
             entries: &[wgpu::BindGroupEntry {
                 binding: 0,
                 resource: wgpu::BindingResource::Buffer(buffer.as_entire_buffer_binding()),
@@ -252,8 +264,19 @@ impl VisibleObject for RectShape {
         self.internal.bind_group = Some(bind_group);
     }
 
+    /// Until the last keyframe, a shape that isn't animated has no duration
     fn duration(&self) -> Option<vide_common::prelude::TimeCode> {
-        None
+        [
+            self.position.last_keyframe_time(),
+            self.rotation.last_keyframe_time(),
+            self.size.last_keyframe_time(),
+            self.pivot.last_keyframe_time(),
+            self.color.last_keyframe_time(),
+        ]
+        .into_iter()
+        .flatten()
+        .max()
+        .filter(|time_code| time_code.value() > 0)
     }
 
     fn set_transform(&mut self, transform: euler::Mat4) {
@@ -268,7 +291,7 @@ impl VisibleObject for RectShape {
     ) {
         let data = self.to_data(
             self.internal.transform.unwrap_or_else(Mat4::identity),
-            local_frame_info.time_code,
+            local_frame_info,
         );
 
         let buffer = self
@@ -295,7 +318,10 @@ impl VisibleObject for RectShape {
             .as_ref()
             .expect("buffer should be set in init()");
 
-        let renderer = renderer();
+        let renderer = wgpu
+            .resources
+            .get::<Self, RectShapeRenderer>(FRAGMENT_COLOR_TARGET)
+            .expect("init() should be called before render()");
 
         let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
             label: Some("RectShape Render Pass"),
@@ -318,4 +344,158 @@ impl VisibleObject for RectShape {
         pass.set_bind_group(1, &wgpu.global_bind_group, &[]);
         pass.draw(0..6, 0..1);
     }
+
+    fn batched(&self) -> bool {
+        true
+    }
+
+    fn draw(
+        &mut self,
+        wgpu: &Wgpu,
+        _frame_info: &vide_common::FrameInfo,
+        _local_frame_info: &vide_common::FrameInfo,
+        pass: &mut wgpu::RenderPass<'_>,
+    ) {
+        let bind_group = self
+            .internal
+            .bind_group
+            .as_ref()
+            .expect("buffer should be set in init()");
+
+        let renderer = wgpu
+            .resources
+            .get::<Self, RectShapeRenderer>(FRAGMENT_COLOR_TARGET)
+            .expect("init() should be called before draw()");
+
+        pass.set_pipeline(&renderer.batch_pipeline);
+        pass.set_vertex_buffer(0, renderer.vertex_buffer.slice(..));
+        pass.set_bind_group(0, bind_group, &[]);
+        pass.set_bind_group(1, &wgpu.global_bind_group, &[]);
+        pass.draw(0..6, 0..1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use vide_animate::KeyframeTiming;
+    use vide_common::{prelude::TimeCode, types::TimeUnit};
+    use vide_project::{
+        clip::{Clip, DurationInference},
+        time_remap::{Looping, TimeRemap},
+        Project,
+    };
+
+    use super::*;
+
+    /// A shape that rotates for 2 seconds
+    fn shape() -> RectShape {
+        RectShape {
+            rotation: AnimatedProperty::builder()
+                .keyframe(KeyframeTiming::Abs(TimeUnit::Seconds(0.0)), 0.0)
+                .keyframe(KeyframeTiming::Abs(TimeUnit::Seconds(2.0)), 90.0)
+                .build(),
+            ..Default::default()
+        }
+    }
+
+    fn clip(time_remap: TimeRemap) -> Clip {
+        let mut clip = Clip::new(1.0..);
+        clip.attach_video(shape());
+        clip.set_time_remap(time_remap);
+        clip
+    }
+
+    #[test]
+    fn duration_is_the_last_keyframe() {
+        assert_eq!(shape().duration(), Some(TimeCode::from_seconds(2.0)));
+        assert_eq!(RectShape::default().duration(), None);
+    }
+
+    #[test]
+    fn natural_duration() {
+        assert_eq!(
+            clip(TimeRemap::new()).natural_duration(),
+            Some(TimeCode::from_seconds(2.0))
+        );
+        assert_eq!(
+            clip(TimeRemap::with_speed(2.0)).natural_duration(),
+            Some(TimeCode::from_seconds(1.0))
+        );
+        assert_eq!(
+            clip(TimeRemap::with_speed(0.5)).natural_duration(),
+            Some(TimeCode::from_seconds(4.0))
+        );
+
+        let looping = |looping| TimeRemap {
+            looping,
+            ..TimeRemap::new()
+        };
+        assert_eq!(
+            clip(looping(Looping::Times(3))).natural_duration(),
+            Some(TimeCode::from_seconds(6.0))
+        );
+        assert_eq!(
+            clip(looping(Looping::Times(0))).natural_duration(),
+            Some(TimeCode::from_seconds(2.0))
+        );
+
+        let mut offset = clip(TimeRemap::new());
+        offset.set_content_offset(TimeCode::from_seconds(0.5));
+        assert_eq!(offset.natural_duration(), Some(TimeCode::from_seconds(1.5)));
+    }
+
+    #[test]
+    fn no_natural_duration() {
+        assert_eq!(
+            clip(TimeRemap::frozen_at(TimeCode::from_seconds(1.0))).natural_duration(),
+            None
+        );
+        assert_eq!(
+            clip(TimeRemap {
+                looping: Looping::Forever,
+                ..TimeRemap::new()
+            })
+            .natural_duration(),
+            None
+        );
+        assert_eq!(
+            clip(TimeRemap::with_curve(AnimatedProperty::with_default(0.0))).natural_duration(),
+            None
+        );
+        assert_eq!(Clip::new(0.0..).natural_duration(), None);
+    }
+
+    #[test]
+    fn infer_durations() {
+        let mut project = Project::new();
+        project.add_clip(clip(TimeRemap::new()));
+
+        let mut off = clip(TimeRemap::new());
+        off.set_duration_inference(DurationInference::Off);
+        project.add_clip(off);
+
+        // Children that play longer keep the clip open
+        let mut parent = clip(TimeRemap::new());
+        parent.add_clip(Clip::new(0.0..5.0));
+        project.add_clip(parent);
+
+        let inferred = project.inferred_duration();
+        project.infer_durations();
+        assert_eq!(project.duration(), inferred);
+
+        // The parent ends with its child instead
+        let ends = project
+            .clips()
+            .map(|clip| clip.range().end())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            ends,
+            [
+                Some(TimeCode::from_seconds(3.0)),
+                None,
+                Some(TimeCode::from_seconds(5.0))
+            ]
+        );
+        assert_eq!(inferred, TimeCode::from_seconds(5.0));
+    }
 }
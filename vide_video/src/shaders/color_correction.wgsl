@@ -0,0 +1,42 @@
+struct ColorCorrectionUniform {
+    exposure: f32,
+    brightness: f32,
+    contrast: f32,
+    saturation: f32,
+    gamma: f32,
+};
+
+@group(0) @binding(0) var source_texture: texture_2d<f32>;
+@group(0) @binding(1) var source_sampler: sampler;
+@group(0) @binding(2) var<uniform> correction: ColorCorrectionUniform;
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+@vertex
+fn vs_main(@builtin(vertex_index) vi: u32) -> VertexOutput {
+    var out: VertexOutput;
+    // Generate a triangle that covers the whole screen
+    let position = vec2<f32>(f32((vi << 1u) & 2u), f32(vi & 2u));
+    out.clip_position = vec4<f32>(position * 2.0 - 1.0, 0.0, 1.0);
+    out.uv = vec2<f32>(position.x, 1.0 - position.y);
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let source = textureSample(source_texture, source_sampler, in.uv);
+
+    var color = source.rgb * exp2(correction.exposure);
+    color = color + correction.brightness;
+    color = (color - 0.5) * correction.contrast + 0.5;
+
+    let luma = dot(color, vec3<f32>(0.2126, 0.7152, 0.0722));
+    color = mix(vec3<f32>(luma), color, correction.saturation);
+
+    color = pow(max(color, vec3<f32>(0.0)), vec3<f32>(1.0 / max(correction.gamma, 0.0001)));
+
+    return vec4<f32>(color, source.a);
+}
@@ -0,0 +1,62 @@
+struct GlobalUniform {
+    ortho_matrix: mat4x4<f32>,
+};
+
+@group(0) @binding(0) var<uniform> global: GlobalUniform;
+
+struct VertexInput {
+    @location(0) position: vec2<f32>,
+    @location(1) uv: vec2<f32>,
+};
+
+struct InstanceInput {
+    @location(2) matrix_0: vec4<f32>,
+    @location(3) matrix_1: vec4<f32>,
+    @location(4) matrix_2: vec4<f32>,
+    @location(5) matrix_3: vec4<f32>,
+    @location(6) color: vec4<f32>,
+    @location(7) shape: u32,
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+    @location(1) color: vec4<f32>,
+    @location(2) @interpolate(flat) shape: u32,
+};
+
+@vertex
+fn vs_main(
+    model: VertexInput,
+    instance: InstanceInput,
+) -> VertexOutput {
+    let transform_matrix = mat4x4<f32>(
+        instance.matrix_0,
+        instance.matrix_1,
+        instance.matrix_2,
+        instance.matrix_3,
+    );
+
+    var out: VertexOutput;
+    out.uv = model.uv;
+    out.color = instance.color;
+    out.shape = instance.shape;
+    out.clip_position = global.ortho_matrix * transform_matrix * vec4<f32>(model.position, 0.0, 1.0);
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    var alpha = in.color.a;
+
+    // Derivatives need uniform control flow, so this is computed for every shape
+    let distance = length(in.uv - 0.5) * 2.0;
+    let edge = fwidth(distance);
+
+    // Ellipse, with an antialiased edge
+    if (in.shape == 1u) {
+        alpha *= 1.0 - smoothstep(1.0 - edge, 1.0, distance);
+    }
+
+    return vec4<f32>(in.color.rgb * alpha, alpha);
+}
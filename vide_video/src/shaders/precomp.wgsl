@@ -0,0 +1,43 @@
+struct PrecompUniform {
+    transform_matrix: mat4x4<f32>,
+};
+
+@group(0) @binding(0) var<uniform> precomp_uniform: PrecompUniform;
+@group(0) @binding(1) var source_texture: texture_2d<f32>;
+@group(0) @binding(2) var source_sampler: sampler;
+
+struct GlobalUniform {
+    ortho_matrix: mat4x4<f32>,
+};
+
+@group(1) @binding(0) var<uniform> global: GlobalUniform;
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+@vertex
+fn vs_main(@builtin(vertex_index) vi: u32) -> VertexOutput {
+    // Two triangles covering the unit square
+    var positions = array<vec2<f32>, 6>(
+        vec2<f32>(0.0, 0.0),
+        vec2<f32>(1.0, 0.0),
+        vec2<f32>(0.0, 1.0),
+        vec2<f32>(0.0, 1.0),
+        vec2<f32>(1.0, 0.0),
+        vec2<f32>(1.0, 1.0),
+    );
+
+    let position = positions[vi];
+
+    var out: VertexOutput;
+    out.clip_position = global.ortho_matrix * precomp_uniform.transform_matrix * vec4<f32>(position, 0.0, 1.0);
+    out.uv = vec2<f32>(position.x, 1.0 - position.y);
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return textureSample(source_texture, source_sampler, in.uv);
+}
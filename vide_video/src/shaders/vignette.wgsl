@@ -0,0 +1,46 @@
+struct VignetteUniform {
+    color: vec4<f32>,
+    intensity: f32,
+    radius: f32,
+    softness: f32,
+    _padding: f32,
+};
+
+@group(0) @binding(0) var source_texture: texture_2d<f32>;
+@group(0) @binding(1) var source_sampler: sampler;
+@group(0) @binding(2) var<uniform> vignette: VignetteUniform;
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+@vertex
+fn vs_main(@builtin(vertex_index) vi: u32) -> VertexOutput {
+    var out: VertexOutput;
+    // Generate a triangle that covers the whole screen
+    let position = vec2<f32>(f32((vi << 1u) & 2u), f32(vi & 2u));
+    out.clip_position = vec4<f32>(position * 2.0 - 1.0, 0.0, 1.0);
+    out.uv = vec2<f32>(position.x, 1.0 - position.y);
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let source = textureSample(source_texture, source_sampler, in.uv);
+
+    // The effect runs on output-sized textures, which can differ from the canvas
+    let size = vec2<f32>(textureDimensions(source_texture));
+    let aspect = size.x / size.y;
+
+    // Distance from the center, where the corners of the frame are at 1
+    var offset = (in.uv - 0.5) * 2.0;
+    offset.x = offset.x * aspect;
+    let distance = length(offset) / length(vec2<f32>(aspect, 1.0));
+
+    let amount = smoothstep(vignette.radius, vignette.radius + vignette.softness, distance)
+        * vignette.intensity
+        * vignette.color.a;
+
+    return vec4<f32>(mix(source.rgb, vignette.color.rgb, amount), source.a);
+}
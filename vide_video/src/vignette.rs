@@ -0,0 +1,88 @@
+use serde::{Deserialize, Serialize};
+use vide_animate::AnimatedProperty;
+use vide_common::{
+    color::Color, config::RenderConfiguration, effect::Effect, render::Wgpu,
+    standards::FRAGMENT_COLOR_TARGET, FrameInfo,
+};
+
+use crate::effect_pass::EffectPass;
+
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy)]
+struct VignetteData {
+    color: [f32; 4],
+    intensity: f32,
+    radius: f32,
+    softness: f32,
+    _padding: f32,
+}
+
+unsafe impl bytemuck::Pod for VignetteData {}
+unsafe impl bytemuck::Zeroable for VignetteData {}
+
+/// Darkens (or tints) the edges of the frame. Effects run on the whole output, so when
+/// the canvas is scaled with bars around it (see `Scaling`), the vignette reaches the
+/// corners of the output rather than those of the canvas.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Vignette {
+    pub color: AnimatedProperty<Color>,
+    pub intensity: AnimatedProperty<f64>,
+    /// Where the vignette starts, `0.0` is the center and `1.0` the corners of the frame
+    pub radius: AnimatedProperty<f64>,
+    pub softness: AnimatedProperty<f64>,
+}
+
+impl Default for Vignette {
+    fn default() -> Self {
+        Self {
+            color: AnimatedProperty::with_default(Color::BLACK),
+            intensity: AnimatedProperty::with_default(0.5),
+            radius: AnimatedProperty::with_default(0.5),
+            softness: AnimatedProperty::with_default(0.5),
+        }
+    }
+}
+
+impl Effect for Vignette {
+    fn init(&mut self, wgpu: &Wgpu, _config: &RenderConfiguration) {
+        wgpu.resources
+            .get_or_init::<Self, _>(FRAGMENT_COLOR_TARGET, || {
+                EffectPass::new(
+                    wgpu,
+                    "Vignette Effect",
+                    include_str!("./shaders/vignette.wgsl"),
+                )
+            });
+    }
+
+    fn apply(
+        &mut self,
+        wgpu: &Wgpu,
+        _frame_info: &FrameInfo,
+        local_frame_info: &FrameInfo,
+        encoder: &mut wgpu::CommandEncoder,
+        source: &wgpu::TextureView,
+        destination: &wgpu::TextureView,
+    ) {
+        let time_code = local_frame_info.time_code;
+
+        let data = VignetteData {
+            color: self.color.evaluate(time_code).into(),
+            intensity: self.intensity.evaluate(time_code) as f32,
+            radius: self.radius.evaluate(time_code) as f32,
+            softness: self.softness.evaluate(time_code) as f32,
+            _padding: 0.0,
+        };
+
+        wgpu.resources
+            .get::<Self, EffectPass>(FRAGMENT_COLOR_TARGET)
+            .expect("init() should be called before apply()")
+            .apply(
+                wgpu,
+                encoder,
+                bytemuck::cast_slice(&[data]),
+                source,
+                destination,
+            );
+    }
+}